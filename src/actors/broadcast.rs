@@ -21,15 +21,16 @@ use crate::{
             BroadcastCodec, BroadcastInboundMessage, BroadcastOutboundMessage, FramedError, RaceSessionType,
             RealtimeCarUpdate, RealtimeUpdate, RegisterConnection, RequestTrackData, TrackData,
         },
-        LapTime,
+        stints::{average_lap, stint_stats, Stint},
     },
     PROGRAM_NAME,
 };
 
 use super::{
     setup_manager::SetupChange,
+    telemetry::PlayerLocation,
     ui::{SessionInfo, Weather},
-    Reset, Router, ShmGameState,
+    Reset, Router, SessionEvent, ShmGameState,
 };
 
 pub struct Broadcast {
@@ -49,8 +50,10 @@ pub struct Broadcast {
     realtime_update: RealtimeUpdate,
     realtime_car_update: RealtimeCarUpdate,
 
-    avg_lap_count: u32,
-    avg_lap_time: LapTime,
+    /// Laps of the session the average lap time is computed from
+    laps: Vec<LapTimeData>,
+    /// Stints detected from the pit lane the laps are grouped by
+    stints: Vec<Stint>,
 }
 
 impl Broadcast {
@@ -64,8 +67,8 @@ impl Broadcast {
             track_data: Default::default(),
             realtime_update: Default::default(),
             realtime_car_update: Default::default(),
-            avg_lap_count: 0,
-            avg_lap_time: Default::default(),
+            laps: Vec::new(),
+            stints: Vec::new(),
         }
     }
 }
@@ -157,6 +160,22 @@ impl Handler<Reset> for Broadcast {
     }
 }
 
+impl Handler<SessionEvent> for Broadcast {
+    type Result = ();
+
+    fn handle(&mut self, msg: SessionEvent, _ctx: &mut Self::Context) -> Self::Result {
+        match msg {
+            // Laps of the last session would be grouped into the stints of the new one
+            SessionEvent::Started(..) => {
+                self.laps.clear();
+                self.stints.clear();
+            }
+            SessionEvent::Stint(stint) => self.stints.push(stint),
+            _ => {}
+        }
+    }
+}
+
 impl StreamHandler<Result<(BroadcastInboundMessage, SocketAddr), FramedError>> for Broadcast {
     #[instrument(skip_all)]
     fn handle(
@@ -201,6 +220,7 @@ impl StreamHandler<Result<(BroadcastInboundMessage, SocketAddr), FramedError>> f
                 }
                 BroadcastInboundMessage::RealtimeCarUpdate(update) => {
                     if update.car_index == self.car_id {
+                        self.update_location(&update);
                         self.update_laps(update)
                     }
                 }
@@ -243,6 +263,8 @@ impl Broadcast {
             self.router.do_send(UiUpdate::Weather(self.session_info.weather));
             self.router
                 .do_send(SetupChange::Weather(self.session_info.weather));
+            self.router
+                .do_send(SessionEvent::Weather(self.session_info.weather));
         }
     }
 
@@ -267,6 +289,14 @@ impl Broadcast {
         }
     }
 
+    /// Send the location of the car to the pit detection of the telemetry
+    fn update_location(&mut self, update: &RealtimeCarUpdate) {
+        if self.realtime_car_update.car_location != update.car_location {
+            self.realtime_car_update.car_location = update.car_location;
+            self.router.do_send(PlayerLocation(update.car_location));
+        }
+    }
+
     fn update_laps(&mut self, update: RealtimeCarUpdate) {
         if self.realtime_car_update.laps != update.laps {
             debug!("realtime update: {:?}", update);
@@ -275,19 +305,7 @@ impl Broadcast {
             if update.laps >= 1 {
                 let last = update.last_lap;
 
-                // Calculate Avg Lap Time and send to fuel calculator
-                if !last.invalid {
-                    let avg = ((self.avg_lap_time.duration().as_millis() * self.avg_lap_count as u128)
-                        + last.laptime.unwrap() as u128)
-                        / (self.avg_lap_count as u128 + 1);
-
-                    self.avg_lap_count += 1;
-                    self.avg_lap_time = Duration::from_millis(avg as u64).into();
-                    self.router
-                        .do_send(FuelMessage::AvgLapTime(self.avg_lap_time.clone()));
-                }
-
-                self.router.do_send(UiUpdate::LapTime(LapTimeData {
+                let lap = LapTimeData {
                     number: update.laps as i32,
                     sectors: last
                         .splits
@@ -298,15 +316,23 @@ impl Broadcast {
                     lap_type: last.lap_type,
                     time: Duration::from_millis(last.laptime.unwrap() as u64).into(),
                     valid: !last.invalid,
-                }));
+                };
+                self.router.do_send(SessionEvent::Lap(lap.clone()));
+                self.router.do_send(UiUpdate::LapTime(lap.clone()));
+
+                // Send the average of the clean laps of the stints to the fuel calculator
+                self.laps.push(lap);
+                if let Some(avg_lap_time) = average_lap(&stint_stats(&self.laps, &self.stints)) {
+                    self.router.do_send(FuelMessage::AvgLapTime(avg_lap_time));
+                }
             }
         }
     }
 
     /// Reset the consumable fields for reuse in a new session
     fn reset(&mut self) {
-        self.avg_lap_count = 0;
-        self.avg_lap_time = Default::default();
+        self.laps.clear();
+        self.stints.clear();
 
         self.session_info = Default::default();
 
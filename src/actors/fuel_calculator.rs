@@ -1,18 +1,22 @@
 use std::time::Duration;
 
+#[cfg(feature = "app")]
 use actix::prelude::*;
 use tracing::debug;
 
 use crate::telemetry::LapTime;
 
+#[cfg(feature = "app")]
 use super::{setup_manager::SetupChange, ui::UiUpdate, Reset, Router};
 
+#[cfg(feature = "app")]
 #[derive(Debug, Clone)]
 pub struct FuelCalculator {
     router: Addr<Router>,
     data: FuelData,
 }
 
+#[cfg(feature = "app")]
 impl FuelCalculator {
     pub fn new(router: Addr<Router>) -> Self {
         Self {
@@ -94,10 +98,12 @@ impl FuelData {
     }
 }
 
+#[cfg(feature = "app")]
 impl Actor for FuelCalculator {
     type Context = Context<Self>;
 }
 
+#[cfg(feature = "app")]
 #[derive(Debug, Clone, Message)]
 #[rtype(result = "()")]
 pub enum FuelMessage {
@@ -111,6 +117,7 @@ pub enum FuelMessage {
     ReserveLaps(i32),
 }
 
+#[cfg(feature = "app")]
 impl Handler<FuelMessage> for FuelCalculator {
     type Result = ();
 
@@ -165,6 +172,7 @@ impl Handler<FuelMessage> for FuelCalculator {
     }
 }
 
+#[cfg(feature = "app")]
 #[derive(Debug, Clone, Message)]
 #[rtype(result = "Option<i32>")]
 pub enum FuelRequest {
@@ -172,6 +180,7 @@ pub enum FuelRequest {
     QualiFuel,
 }
 
+#[cfg(feature = "app")]
 impl Handler<FuelRequest> for FuelCalculator {
     type Result = Option<i32>;
 
@@ -183,6 +192,7 @@ impl Handler<FuelRequest> for FuelCalculator {
     }
 }
 
+#[cfg(feature = "app")]
 impl Handler<Reset> for FuelCalculator {
     type Result = ();
 
@@ -1,8 +1,15 @@
+#[cfg(feature = "app")]
 pub mod broadcast;
 pub mod fuel_calculator;
+pub mod motec;
+pub mod pit_loss;
+#[cfg(feature = "app")]
 pub mod router;
+pub mod sessions;
 pub mod setup_manager;
+#[cfg(feature = "app")]
 pub mod telemetry;
 pub mod ui;
 
+#[cfg(feature = "app")]
 pub use router::*;
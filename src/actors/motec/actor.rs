@@ -0,0 +1,136 @@
+use std::{path::PathBuf, time::Duration};
+
+use actix::prelude::*;
+use chrono::Local;
+use tracing::{debug, error, info};
+
+use crate::actors::{ui::UiUpdate, Router, SessionEvent};
+
+use super::{archive_folder, MotecRetention, MotecSession, PendingSessions};
+
+/// Time to wait after a session ended for ACC to finish writing its MoTeC files
+const ARCHIVE_DELAY: Duration = Duration::from_secs(10);
+
+/// Moves the MoTeC files ACC writes for the telemetry laps of a setup out of its folder and into
+/// an archive sorted by car, track, date and session type
+#[derive(Debug)]
+pub struct MotecArchive {
+    router: Addr<Router>,
+    /// Retention of the archive, `None` while archiving is turned off
+    retention: Option<MotecRetention>,
+    /// Session currently driven, with its laps so far
+    session: Option<MotecSession>,
+    /// Sessions that ended and wait for ACC to finish writing their files
+    pending: PendingSessions,
+
+    motec_folder: PathBuf,
+    archive_folder: PathBuf,
+}
+
+impl MotecArchive {
+    pub fn new(router: Addr<Router>) -> Self {
+        let mut motec_folder = crate::documents_folder();
+        motec_folder.push("Assetto Corsa Competizione");
+        motec_folder.push("MoTeC");
+
+        let archive_folder = archive_folder();
+        MotecArchive {
+            router,
+            retention: None,
+            session: None,
+            pending: PendingSessions::open(&archive_folder),
+            motec_folder,
+            archive_folder,
+        }
+    }
+
+    /// Archive the pending sessions that ended long enough ago, or drop them if archiving is off
+    fn archive_pending(&mut self) {
+        let ended = self.pending.take_ended(Local::now() - ARCHIVE_DELAY);
+        if ended.is_empty() {
+            return;
+        }
+
+        ended.into_iter().for_each(|session| self.archive(session));
+        if let Err(e) = self.pending.save() {
+            error!("failed to save pending motec sessions: {e}");
+        }
+    }
+
+    fn archive(&self, mut session: MotecSession) {
+        let Some(retention) = self.retention else {
+            return;
+        };
+
+        match session.archive(&self.motec_folder, &self.archive_folder) {
+            Ok(0) => debug!("no motec files written in the {} session", session.session_type),
+            Ok(files) => {
+                info!(
+                    "archived {files} motec files of the {} session",
+                    session.session_type
+                );
+                self.router.do_send(UiUpdate::MotecSession(session));
+            }
+            Err(e) => error!("failed to archive motec files: {e}"),
+        }
+
+        match retention.apply(&self.archive_folder, Local::now()) {
+            Ok(0) => {}
+            Ok(removed) => info!("removed {removed} archived motec sessions"),
+            Err(e) => error!("failed to apply motec retention: {e}"),
+        }
+    }
+}
+
+impl Actor for MotecArchive {
+    type Context = Context<Self>;
+}
+
+impl Handler<SessionEvent> for MotecArchive {
+    type Result = ();
+
+    fn handle(&mut self, msg: SessionEvent, ctx: &mut Self::Context) -> Self::Result {
+        match msg {
+            SessionEvent::Started(car, track, session_type) => {
+                self.session = Some(MotecSession::new(&car, &track, session_type, Local::now()));
+            }
+            SessionEvent::Lap(lap) => {
+                if let Some(session) = &mut self.session {
+                    session.laps.push(lap);
+                }
+            }
+            SessionEvent::Ended => {
+                if let Some(mut session) = self.session.take() {
+                    session.ended = Local::now();
+                    self.pending.sessions.push(session);
+                    if let Err(e) = self.pending.save() {
+                        error!("failed to save pending motec sessions: {e}");
+                    }
+                    ctx.run_later(ARCHIVE_DELAY, |archive, _| archive.archive_pending());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, Message)]
+#[rtype(result = "()")]
+pub enum MotecMessage {
+    /// Archive the MoTeC files of every session with the retention, `None` turns archiving off
+    Retention(Option<MotecRetention>),
+}
+
+impl Handler<MotecMessage> for MotecArchive {
+    type Result = ();
+
+    fn handle(&mut self, msg: MotecMessage, ctx: &mut Self::Context) -> Self::Result {
+        match msg {
+            MotecMessage::Retention(retention) => {
+                self.retention = retention;
+                // Sessions left over from the last run are archived once the retention is known
+                ctx.run_later(ARCHIVE_DELAY, |archive, _| archive.archive_pending());
+            }
+        }
+    }
+}
@@ -0,0 +1,338 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{debug, error};
+
+use crate::{actors::ui::LapTimeData, telemetry::shm::SessionType};
+
+/// Extensions of the telemetry files ACC writes into its MoTeC folder
+const MOTEC_EXTENSIONS: [&str; 2] = ["ld", "ldx"];
+/// Suffix of the file describing an archived session, next to its MoTeC files
+const SESSION_SUFFIX: &str = ".session.json";
+/// File in the archive keeping the sessions that ended but weren't archived yet
+const PENDING_FILE: &str = "pending.json";
+
+#[derive(Debug, Error)]
+pub enum MotecError {
+    #[error("io error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("failed to parse session file: {0}")]
+    ParseError(#[from] serde_json::Error),
+}
+
+/// How long archived sessions are kept, sessions are never removed without a limit
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MotecRetention {
+    /// Days to keep the sessions for after they ended
+    pub days: Option<u32>,
+    /// Newest sessions to keep per car and track
+    pub sessions: Option<u32>,
+}
+
+/// Session the MoTeC files were written in, saved next to them in the archive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MotecSession {
+    pub car: String,
+    pub track: String,
+    pub session_type: SessionType,
+    pub started: DateTime<Local>,
+    pub ended: DateTime<Local>,
+    /// Names of the archived files in the session folder
+    pub files: Vec<String>,
+    /// Laps of the session as shown in the app
+    pub laps: Vec<LapTimeData>,
+}
+
+impl MotecSession {
+    pub fn new(car: &str, track: &str, session_type: SessionType, started: DateTime<Local>) -> Self {
+        MotecSession {
+            car: car.to_owned(),
+            track: track.to_owned(),
+            session_type,
+            started,
+            ended: started,
+            files: Vec::new(),
+            laps: Vec::new(),
+        }
+    }
+
+    /// Folder of the session in the archive, `car/track/date/session type`
+    pub fn folder(&self, archive: &Path) -> PathBuf {
+        archive
+            .join(&self.car)
+            .join(&self.track)
+            .join(self.started.format("%Y-%m-%d").to_string())
+            .join(self.session_type.to_string())
+    }
+
+    /// Path of the file describing the session, several sessions of a day can share a folder
+    pub fn path(&self, archive: &Path) -> PathBuf {
+        self.folder(archive)
+            .join(format!("{}{SESSION_SUFFIX}", self.started.format("%H-%M-%S")))
+    }
+
+    /// Session saved in a session file of the archive
+    pub fn read(path: &Path) -> Result<Self, MotecError> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Move the MoTeC files ACC wrote since the session started into the archive and save the
+    /// session next to them, returns the number of files moved
+    pub fn archive(&mut self, motec_folder: &Path, archive: &Path) -> Result<usize, MotecError> {
+        let started = SystemTime::from(self.started);
+        let files: Vec<PathBuf> = std::fs::read_dir(motec_folder)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| MOTEC_EXTENSIONS.iter().any(|e| extension == *e))
+            })
+            .filter(|path| {
+                std::fs::metadata(path)
+                    .and_then(|metadata| metadata.modified())
+                    .is_ok_and(|modified| modified >= started)
+            })
+            .collect();
+        if files.is_empty() {
+            return Ok(0);
+        }
+
+        let folder = self.folder(archive);
+        std::fs::create_dir_all(&folder)?;
+        for file in &files {
+            let Some(name) = file.file_name() else {
+                continue;
+            };
+            let target = folder.join(name);
+            debug!("archiving motec file {:?} to {:?}", file, target);
+
+            // Renaming fails when the archive is on another drive than ACC's folder
+            if std::fs::rename(file, &target).is_err() {
+                std::fs::copy(file, &target)?;
+                std::fs::remove_file(file)?;
+            }
+            self.files.push(name.to_string_lossy().into_owned());
+        }
+
+        std::fs::write(self.path(archive), serde_json::to_string_pretty(self)?)?;
+
+        Ok(files.len())
+    }
+
+    /// Remove the session file and the MoTeC files of the session
+    fn remove(&self, path: &Path) -> Result<(), MotecError> {
+        let folder = path.parent().unwrap_or(Path::new(""));
+        for file in &self.files {
+            let _ = std::fs::remove_file(folder.join(file));
+        }
+        std::fs::remove_file(path)?;
+
+        // Session type and date folders are removed once they are empty
+        let _ = std::fs::remove_dir(folder);
+        if let Some(date) = folder.parent() {
+            let _ = std::fs::remove_dir(date);
+        }
+
+        Ok(())
+    }
+}
+
+/// Sessions that ended while ACC may still be writing their MoTeC files, saved so they are
+/// archived once the app starts again if it was closed before they were
+#[derive(Debug, Default)]
+pub struct PendingSessions {
+    path: PathBuf,
+    pub sessions: Vec<MotecSession>,
+}
+
+impl PendingSessions {
+    pub fn open(archive: &Path) -> Self {
+        let path = archive.join(PENDING_FILE);
+        let sessions = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| {
+                serde_json::from_str(&data)
+                    .inspect_err(|e| error!("failed to parse pending motec sessions {:?}: {e}", path))
+                    .ok()
+            })
+            .unwrap_or_default();
+
+        PendingSessions { path, sessions }
+    }
+
+    pub fn save(&self) -> Result<(), MotecError> {
+        if self.sessions.is_empty() {
+            return match std::fs::remove_file(&self.path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            };
+        }
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&self.sessions)?)?;
+        Ok(())
+    }
+
+    /// Take the sessions that ended before the time, ACC is done writing their files
+    pub fn take_ended(&mut self, before: DateTime<Local>) -> Vec<MotecSession> {
+        let (ended, pending) = self
+            .sessions
+            .drain(..)
+            .partition(|session| session.ended <= before);
+        self.sessions = pending;
+        ended
+    }
+}
+
+impl MotecRetention {
+    /// Remove the archived sessions beyond the retention, returns the number of removed sessions
+    pub fn apply(&self, archive: &Path, now: DateTime<Local>) -> Result<usize, MotecError> {
+        if self.days.is_none() && self.sessions.is_none() {
+            return Ok(0);
+        }
+
+        let mut tracks: BTreeMap<(String, String), Vec<(PathBuf, MotecSession)>> = BTreeMap::new();
+        for path in session_files(archive) {
+            // A broken session file is left alone, it shouldn't stop the retention of the others
+            let session = match MotecSession::read(&path) {
+                Ok(session) => session,
+                Err(e) => {
+                    error!("skipping archived motec session {:?}: {e}", path);
+                    continue;
+                }
+            };
+            tracks
+                .entry((session.car.clone(), session.track.clone()))
+                .or_default()
+                .push((path, session));
+        }
+
+        let mut removed = 0;
+        for sessions in tracks.values_mut() {
+            sessions.sort_by_key(|(_, session)| std::cmp::Reverse(session.started));
+            for (index, (path, session)) in sessions.iter().enumerate() {
+                let expired = self
+                    .days
+                    .is_some_and(|days| session.ended < now - Duration::days(days as i64));
+                let excess = self.sessions.is_some_and(|sessions| index >= sessions as usize);
+                if expired || excess {
+                    debug!("removing archived motec session {:?}", path);
+                    session.remove(path)?;
+                    removed += 1;
+                }
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+/// Session files anywhere in the archive
+fn session_files(folder: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(folder) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .flat_map(|path| {
+            if path.is_dir() {
+                session_files(&path)
+            } else if path.to_string_lossy().ends_with(SESSION_SUFFIX) {
+                vec![path]
+            } else {
+                Vec::new()
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn test_motec_archive() -> Result<(), MotecError> {
+    let folder = crate::TestFolder::new("motec");
+    let motec = folder.join("MoTeC");
+    let archive = folder.join("Archive");
+    std::fs::create_dir_all(&motec)?;
+
+    let now = Local::now();
+    let mut sessions = Vec::new();
+    for (days, session_type) in [
+        (10, SessionType::Practice),
+        (1, SessionType::Qualify),
+        (0, SessionType::Race),
+    ] {
+        let started = now - Duration::days(days) - Duration::seconds(5);
+        let mut session = MotecSession::new("bmw_m4_gt3", "spa", session_type, started);
+        session.ended = started;
+        session.laps.push(LapTimeData {
+            number: 1,
+            ..Default::default()
+        });
+
+        let name = format!("spa-bmw_m4_gt3-{days}");
+        std::fs::write(motec.join(format!("{name}.ld")), "ld")?;
+        std::fs::write(motec.join(format!("{name}.ldx")), "ldx")?;
+        std::fs::write(motec.join("notes.txt"), "not telemetry")?;
+        assert_eq!(session.archive(&motec, &archive)?, 2);
+        sessions.push(session);
+    }
+
+    let race = &sessions[2];
+    assert_eq!(
+        race.folder(&archive),
+        archive
+            .join("bmw_m4_gt3")
+            .join("spa")
+            .join(now.format("%Y-%m-%d").to_string())
+            .join("Race")
+    );
+    assert!(race.folder(&archive).join("spa-bmw_m4_gt3-0.ld").exists());
+    assert!(motec.join("notes.txt").exists());
+    assert_eq!(&MotecSession::read(&race.path(&archive))?, race);
+
+    let mut pending = PendingSessions::open(&archive);
+    pending.sessions.push(sessions[0].clone());
+    pending.sessions.push(race.clone());
+    pending.save()?;
+    let mut pending = PendingSessions::open(&archive);
+    assert_eq!(
+        pending.take_ended(now - Duration::days(1)),
+        vec![sessions[0].clone()]
+    );
+    assert_eq!(pending.sessions, vec![race.clone()]);
+    pending.take_ended(now);
+    pending.save()?;
+    assert!(!archive.join(PENDING_FILE).exists());
+
+    // Files written before the session started belong to another one
+    std::fs::write(motec.join("old.ld"), "ld")?;
+    let mut late = MotecSession::new("bmw_m4_gt3", "spa", SessionType::Race, now + Duration::hours(1));
+    assert_eq!(late.archive(&motec, &archive)?, 0);
+
+    std::fs::write(archive.join("broken.session.json"), "not a session")?;
+    let retention = MotecRetention {
+        days: Some(7),
+        sessions: None,
+    };
+    assert_eq!(retention.apply(&archive, now)?, 1);
+    assert!(!sessions[0].folder(&archive).exists());
+
+    let retention = MotecRetention {
+        days: None,
+        sessions: Some(1),
+    };
+    assert_eq!(retention.apply(&archive, now)?, 1);
+    assert!(!sessions[1].path(&archive).exists());
+    assert!(race.path(&archive).exists());
+
+    Ok(())
+}
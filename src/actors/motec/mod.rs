@@ -0,0 +1,13 @@
+use std::path::PathBuf;
+
+#[cfg(feature = "app")]
+mod actor;
+mod archive;
+#[cfg(feature = "app")]
+pub use actor::*;
+pub use archive::*;
+
+/// Folder the MoTeC files are archived in
+pub fn archive_folder() -> PathBuf {
+    crate::data_folder().join("MoTeC")
+}
@@ -0,0 +1,86 @@
+use std::path::Path;
+
+use actix::prelude::*;
+use tracing::{debug, error, warn};
+
+use crate::actors::{ui::UiUpdate, Router, SessionEvent};
+
+use super::{PitLoss, PitLossMeasurement, PitLosses};
+
+/// Measures the time spent and lost in the pits and keeps a running average per track
+#[derive(Debug)]
+pub struct PitLossTracker {
+    router: Addr<Router>,
+    losses: PitLosses,
+    /// Track of the current session
+    track: Option<String>,
+    measurement: PitLossMeasurement,
+}
+
+impl PitLossTracker {
+    pub fn new(router: Addr<Router>, path: &Path) -> Self {
+        PitLossTracker {
+            router,
+            losses: PitLosses::open(path),
+            track: None,
+            measurement: PitLossMeasurement::default(),
+        }
+    }
+
+    /// Update the pit loss of the track, returns false when the update was rejected
+    fn update(&mut self, f: impl FnOnce(&mut PitLoss) -> bool) -> bool {
+        let Some(track) = &self.track else {
+            return false;
+        };
+
+        let pit_loss = self.losses.tracks.entry(track.clone()).or_default();
+        if !f(pit_loss) {
+            return false;
+        }
+        debug!("pit loss at {track}: {pit_loss:?}");
+        self.router.do_send(UiUpdate::PitLoss(Some(pit_loss.clone())));
+
+        if let Err(e) = self.losses.save() {
+            error!("failed to save pit losses: {e}");
+        }
+        true
+    }
+}
+
+impl Actor for PitLossTracker {
+    type Context = Context<Self>;
+}
+
+impl Handler<SessionEvent> for PitLossTracker {
+    type Result = ();
+
+    fn handle(&mut self, msg: SessionEvent, _ctx: &mut Self::Context) -> Self::Result {
+        match msg {
+            SessionEvent::Started(_car, track, _session_type) => {
+                self.router
+                    .do_send(UiUpdate::PitLoss(self.losses.tracks.get(&track).cloned()));
+                self.track = Some(track);
+                self.measurement = PitLossMeasurement::default();
+            }
+            SessionEvent::Stint(mut stint) => {
+                if let Some(pit_stop) = &stint.pit_stop {
+                    if !self.update(|pit_loss| pit_loss.add_stop(pit_stop)) {
+                        // Its in- and out-lap don't tell the time lost either
+                        warn!("implausible pit stop: {pit_stop:?}");
+                        stint.pit_stop = None;
+                    }
+                }
+                self.measurement.add_stint(stint);
+            }
+            SessionEvent::Lap(lap) => {
+                if let Some((pit_stop, loss)) = self.measurement.add_lap(lap) {
+                    self.update(|pit_loss| {
+                        pit_loss.add_loss(&pit_stop, loss);
+                        true
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::error;
+
+use crate::{
+    actors::ui::LapTimeData,
+    telemetry::{
+        pit::PitStop,
+        stints::{average_lap, stint_stats, Stint},
+    },
+};
+
+/// Share the time driving through the pit lane may differ from its average before a stop is rejected
+const MAX_DEVIATION: f32 = 0.5;
+/// Longest time driving through the pit lane of any track in seconds, the first stop of a track
+/// has no average to check against yet
+const MAX_TRANSIT: f32 = 90.0;
+/// Longest time standing in the box in seconds, refuelling a full tank with a tyre change and repairs
+const MAX_STATIONARY: f32 = 180.0;
+
+#[derive(Debug, Error)]
+pub enum PitLossError {
+    #[error("io error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("failed to parse pit losses: {0}")]
+    ParseError(#[from] serde_json::Error),
+}
+
+/// Average of every value added so far, in seconds
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RunningAverage {
+    pub count: u32,
+    pub average: f32,
+}
+
+impl RunningAverage {
+    pub fn add(&mut self, value: f32) {
+        self.count += 1;
+        self.average += (value - self.average) / self.count as f32;
+    }
+
+    pub fn get(&self) -> Option<f32> {
+        (self.count > 0).then_some(self.average)
+    }
+}
+
+/// Time spent and lost in the pits of a track
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PitLoss {
+    /// Time in the pit lane of drive-throughs
+    pub drive_through: RunningAverage,
+    /// Time in the pit lane of stops
+    pub stop: RunningAverage,
+    /// Time standing in the pit box
+    pub stationary: RunningAverage,
+    /// In- and out-lap against two laps at pace without the time standing in the box,
+    /// what a drive-through costs
+    pub lane_loss: RunningAverage,
+    /// In- and out-lap of stops against two laps at pace
+    pub stop_loss: RunningAverage,
+}
+
+impl PitLoss {
+    /// Add the times of the stop, returns false when they are implausible against the averages,
+    /// e.g. after a stop in the garage
+    pub fn add_stop(&mut self, pit_stop: &PitStop) -> bool {
+        let driving = pit_stop
+            .lane_time
+            .saturating_sub(pit_stop.stationary_time)
+            .as_secs_f32();
+        if driving > MAX_TRANSIT || pit_stop.stationary_time.as_secs_f32() > MAX_STATIONARY {
+            return false;
+        }
+        if self
+            .transit()
+            .is_some_and(|transit| (driving - transit).abs() > transit * MAX_DEVIATION)
+        {
+            return false;
+        }
+
+        let lane_time = pit_stop.lane_time.as_secs_f32();
+        if pit_stop.is_drive_through() {
+            self.drive_through.add(lane_time);
+        } else {
+            self.stop.add(lane_time);
+            self.stationary.add(pit_stop.stationary_time.as_secs_f32());
+        }
+        true
+    }
+
+    /// Average time driving through the pit lane without stopping
+    pub fn transit(&self) -> Option<f32> {
+        self.drive_through
+            .get()
+            .or_else(|| Some(self.stop.get()? - self.stationary.get()?))
+    }
+
+    /// Add the time lost in seconds by the in- and out-lap of the stop
+    pub fn add_loss(&mut self, pit_stop: &PitStop, loss: f32) {
+        self.lane_loss.add(loss - pit_stop.stationary_time.as_secs_f32());
+        if !pit_stop.is_drive_through() {
+            self.stop_loss.add(loss);
+        }
+    }
+
+    /// Expected time lost by a stop standing in the box for the seconds
+    pub fn estimate(&self, stationary: f32) -> Option<f32> {
+        self.lane_loss.get().map(|loss| loss + stationary)
+    }
+}
+
+/// Pit losses of every track, saved to a file
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PitLosses {
+    #[serde(skip)]
+    path: PathBuf,
+    pub tracks: BTreeMap<String, PitLoss>,
+}
+
+impl PitLosses {
+    pub fn open(path: &Path) -> Self {
+        let losses = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|data| {
+                serde_json::from_str::<PitLosses>(&data)
+                    .inspect_err(|e| error!("failed to parse pit losses {:?}: {e}", path))
+                    .ok()
+            })
+            .unwrap_or_default();
+
+        PitLosses {
+            path: path.to_owned(),
+            ..losses
+        }
+    }
+
+    pub fn save(&self) -> Result<(), PitLossError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Laps and stints of a session to measure the time lost by a pit stop once its out-lap is completed
+#[derive(Debug, Default, Clone)]
+pub struct PitLossMeasurement {
+    laps: Vec<LapTimeData>,
+    stints: Vec<Stint>,
+}
+
+impl PitLossMeasurement {
+    pub fn add_stint(&mut self, stint: Stint) {
+        self.stints.push(stint);
+    }
+
+    /// Add a completed lap, returns the stop with the seconds it lost against two laps at pace
+    /// when the lap is its out-lap
+    pub fn add_lap(&mut self, lap: LapTimeData) -> Option<(PitStop, f32)> {
+        let number = lap.number;
+        self.laps.push(lap);
+
+        let pit_stop = self
+            .stints
+            .iter()
+            .find(|stint| stint.first_lap == number)
+            .and_then(|stint| stint.pit_stop.clone())?;
+
+        // The pit lane usually spans the finish line, the in-lap loses time as well
+        let lap_time = |number: i32| {
+            self.laps
+                .iter()
+                .find(|lap| lap.number == number)
+                .map(|lap| lap.time.duration().as_secs_f32())
+        };
+        let in_lap = lap_time(number - 1)?;
+        let out_lap = lap_time(number)?;
+        let pace = average_lap(&stint_stats(&self.laps, &self.stints))?
+            .duration()
+            .as_secs_f32();
+
+        Some((pit_stop, in_lap + out_lap - 2.0 * pace))
+    }
+}
+
+#[test]
+fn test_pit_loss() -> Result<(), PitLossError> {
+    use std::time::Duration;
+
+    use crate::telemetry::broadcast::LapType;
+
+    let lap = |number: i32, millis: u64, lap_type: LapType| LapTimeData {
+        number,
+        time: Duration::from_millis(millis).into(),
+        valid: true,
+        lap_type,
+        ..Default::default()
+    };
+    let pit_stop = PitStop {
+        lane_time: Duration::from_secs(60),
+        stationary_time: Duration::from_secs(30),
+        fuel_added: 40.0,
+        tyre_sets: (1, 2),
+    };
+
+    let mut measurement = PitLossMeasurement::default();
+    measurement.add_stint(Stint {
+        first_lap: 1,
+        tyre_set: 1,
+        pit_stop: None,
+    });
+    assert_eq!(measurement.add_lap(lap(1, 110_000, LapType::Outlap)), None);
+    assert_eq!(measurement.add_lap(lap(2, 100_000, LapType::Regular)), None);
+    assert_eq!(measurement.add_lap(lap(3, 102_000, LapType::Regular)), None);
+    assert_eq!(measurement.add_lap(lap(4, 125_000, LapType::Inlap)), None);
+    measurement.add_stint(Stint {
+        first_lap: 5,
+        tyre_set: 2,
+        pit_stop: Some(pit_stop.clone()),
+    });
+    let (stop, loss) = measurement.add_lap(lap(5, 135_000, LapType::Outlap)).unwrap();
+    assert_eq!(stop, pit_stop);
+    assert_eq!(loss, 58.0);
+
+    // A garage visit as the first stop of a track doesn't become the average
+    let garage = PitStop {
+        lane_time: Duration::from_secs(300),
+        ..Default::default()
+    };
+    let mut pit_loss = PitLoss::default();
+    assert!(!pit_loss.add_stop(&garage));
+    assert_eq!(pit_loss, PitLoss::default());
+
+    assert_eq!(pit_loss.estimate(0.0), None);
+    assert!(pit_loss.add_stop(&stop));
+    pit_loss.add_loss(&stop, loss);
+    assert_eq!(pit_loss.transit(), Some(30.0));
+    let drive_through = PitStop {
+        lane_time: Duration::from_secs(26),
+        ..Default::default()
+    };
+    assert!(pit_loss.add_stop(&drive_through));
+    pit_loss.add_loss(&drive_through, 24.0);
+    assert_eq!(pit_loss.drive_through.get(), Some(26.0));
+    assert_eq!(pit_loss.stop.get(), Some(60.0));
+    assert_eq!(pit_loss.stationary.get(), Some(30.0));
+    assert_eq!(pit_loss.stop_loss.get(), Some(58.0));
+    assert_eq!(pit_loss.lane_loss.count, 2);
+    assert_eq!(pit_loss.estimate(20.0), Some(46.0));
+
+    // Once there is an average, a slow trip through the lane is rejected against it
+    let slow = PitStop {
+        lane_time: Duration::from_secs(50),
+        ..Default::default()
+    };
+    let averages = pit_loss.clone();
+    assert!(!pit_loss.add_stop(&slow));
+    let repairs = PitStop {
+        lane_time: Duration::from_secs(630),
+        stationary_time: Duration::from_secs(600),
+        ..Default::default()
+    };
+    assert!(!pit_loss.add_stop(&repairs));
+    assert_eq!(pit_loss, averages);
+
+    let folder = crate::TestFolder::new("pit-loss");
+    let path = folder.join("pit_loss.json");
+    let mut losses = PitLosses::open(&path);
+    losses.tracks.insert("spa".to_owned(), pit_loss.clone());
+    losses.save()?;
+    assert_eq!(PitLosses::open(&path).tracks.get("spa"), Some(&pit_loss));
+
+    Ok(())
+}
@@ -0,0 +1,6 @@
+#[cfg(feature = "app")]
+mod actor;
+mod losses;
+#[cfg(feature = "app")]
+pub use actor::*;
+pub use losses::*;
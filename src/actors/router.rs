@@ -1,20 +1,27 @@
 use actix::prelude::*;
 use tracing::debug;
 
+use crate::telemetry::{shm::SessionType, stints::Stint, LapWheels};
+
 use super::{
     broadcast::Broadcast,
     fuel_calculator::{FuelCalculator, FuelMessage},
-    setup_manager::{SetupChange, SetupManager},
-    telemetry::{CarID, Telemetry},
-    ui::{UiState, UiUpdate},
+    motec::{MotecArchive, MotecMessage},
+    pit_loss::PitLossTracker,
+    sessions::{sessions_folder, SessionQuery, SessionRecorder},
+    setup_manager::{Setup, SetupChange, SetupManager},
+    telemetry::{CarID, PlayerLocation, Telemetry},
+    ui::{LapTimeData, UiState, UiUpdate, Weather},
 };
 
 pub struct Router {
-    #[allow(unused)]
     telemetry: Addr<Telemetry>,
     broadcast: Addr<Broadcast>,
     setup_manager: Addr<SetupManager>,
     fuel_calculator: Addr<FuelCalculator>,
+    motec_archive: Addr<MotecArchive>,
+    session_recorder: Addr<SessionRecorder>,
+    pit_loss: Addr<PitLossTracker>,
     clients: Vec<Addr<UiState>>,
 }
 
@@ -29,12 +36,19 @@ impl Router {
 
             let setup_manager = SetupManager::new(ctx.address()).start();
             let fuel_calculator = FuelCalculator::new(ctx.address()).start();
+            let motec_archive = MotecArchive::new(ctx.address()).start();
+            let session_recorder = SessionRecorder::new(ctx.address(), &sessions_folder()).start();
+            let pit_loss =
+                PitLossTracker::new(ctx.address(), &crate::data_folder().join("pit_loss.json")).start();
 
             Router {
                 telemetry,
                 broadcast,
                 setup_manager,
                 fuel_calculator,
+                motec_archive,
+                session_recorder,
+                pit_loss,
                 clients: Vec::new(),
             }
         })
@@ -84,6 +98,54 @@ impl Handler<ShmGameState> for Router {
     }
 }
 
+/// Sessions of the player, a new one starts whenever the session type changes
+#[derive(Debug, Clone, Message)]
+#[rtype(result = "()")]
+pub enum SessionEvent {
+    /// Session of the car and track started
+    Started(String, String, SessionType),
+    Weather(Weather),
+    /// Lap completed in the session
+    Lap(LapTimeData),
+    /// Tyre and brake stats of a completed lap
+    Wheels(LapWheels),
+    /// Stint started at the start of the session or by leaving the pit lane
+    Stint(Stint),
+    FuelPerLap(f32),
+    /// Adjusted setup written to ACC by its file name
+    Setup(String, Setup),
+    Ended,
+}
+
+impl Handler<SessionEvent> for Router {
+    type Result = ();
+
+    fn handle(&mut self, msg: SessionEvent, _ctx: &mut Self::Context) -> Self::Result {
+        debug!(handler = "SessionEvent", msg = ?msg);
+        self.broadcast.do_send(msg.clone());
+        self.setup_manager.do_send(msg.clone());
+        self.motec_archive.do_send(msg.clone());
+        self.session_recorder.do_send(msg.clone());
+        self.pit_loss.do_send(msg);
+    }
+}
+
+impl Handler<SessionQuery> for Router {
+    type Result = ();
+
+    fn handle(&mut self, msg: SessionQuery, _ctx: &mut Self::Context) -> Self::Result {
+        self.session_recorder.do_send(msg);
+    }
+}
+
+impl Handler<MotecMessage> for Router {
+    type Result = ();
+
+    fn handle(&mut self, msg: MotecMessage, _ctx: &mut Self::Context) -> Self::Result {
+        self.motec_archive.do_send(msg);
+    }
+}
+
 impl Handler<UiUpdate> for Router {
     type Result = ();
 
@@ -146,6 +208,14 @@ impl Handler<Reset> for Router {
     }
 }
 
+impl Handler<PlayerLocation> for Router {
+    type Result = ();
+
+    fn handle(&mut self, msg: PlayerLocation, _ctx: &mut Self::Context) -> Self::Result {
+        self.telemetry.do_send(msg);
+    }
+}
+
 impl Handler<CarID> for Router {
     type Result = ResponseFuture<i16>;
 
@@ -0,0 +1,201 @@
+use std::{collections::BTreeMap, path::Path};
+
+use actix::prelude::*;
+use chrono::Local;
+use tracing::{debug, error};
+
+use crate::{
+    actors::{
+        setup_manager::Setup,
+        ui::{UiUpdate, Weather},
+        Router, SessionEvent,
+    },
+    telemetry::shm::SessionType,
+};
+
+use super::{SessionRecord, SessionStore};
+
+/// Writes every session of the player to the session store while it is driven
+#[derive(Debug)]
+pub struct SessionRecorder {
+    router: Addr<Router>,
+    store: SessionStore,
+    /// Id of the session being recorded
+    session: Option<String>,
+    /// Laps recorded in the current session, sessions without laps are not kept
+    laps: usize,
+    /// Car and track of the last session
+    car_track: Option<(String, String)>,
+
+    /// Latest weather, fuel usage and setups, recorded again at the start of the next session
+    weather: Option<Weather>,
+    fuel_per_lap: Option<f32>,
+    setups: BTreeMap<String, Setup>,
+}
+
+impl SessionRecorder {
+    pub fn new(router: Addr<Router>, folder: &Path) -> Self {
+        SessionRecorder {
+            router,
+            store: SessionStore::new(folder),
+            session: None,
+            laps: 0,
+            car_track: None,
+            weather: None,
+            fuel_per_lap: None,
+            setups: BTreeMap::new(),
+        }
+    }
+
+    fn record(&self, record: SessionRecord) {
+        let Some(id) = &self.session else {
+            return;
+        };
+
+        if let Err(e) = self.store.append(id, &record) {
+            error!("failed to record session {id}: {e}");
+        }
+    }
+
+    fn start(&mut self, car: String, track: String, session_type: SessionType) {
+        self.end();
+
+        // Setups of another car or track were not used in this session
+        let car_track = Some((car.clone(), track.clone()));
+        if self.car_track != car_track {
+            self.car_track = car_track;
+            self.setups.clear();
+        }
+
+        let time = Local::now();
+        let id = self.store.new_id(&time);
+        debug!("recording session {id}");
+        self.session = Some(id);
+        self.laps = 0;
+
+        self.record(SessionRecord::Started {
+            car,
+            track,
+            session_type,
+            time,
+        });
+        if let Some(weather) = self.weather {
+            self.record(SessionRecord::Weather { time, weather });
+        }
+        if let Some(fuel_per_lap) = self.fuel_per_lap {
+            self.record(SessionRecord::Fuel { time, fuel_per_lap });
+        }
+        for (name, setup) in &self.setups {
+            self.record(SessionRecord::Setup {
+                name: name.clone(),
+                setup: Box::new(setup.clone()),
+            });
+        }
+    }
+
+    fn end(&mut self) {
+        if let Some(id) = self.session.take() {
+            if self.laps == 0 {
+                let _ = self
+                    .store
+                    .remove(&id)
+                    .inspect_err(|e| error!("failed to remove empty session {id}: {e}"));
+            } else {
+                if let Err(e) = self.store.end(&id, Local::now()) {
+                    error!("failed to end session {id}: {e}");
+                }
+                self.list();
+            }
+        }
+    }
+
+    fn list(&self) {
+        self.router.do_send(UiUpdate::Sessions(self.store.sessions()));
+    }
+}
+
+impl Actor for SessionRecorder {
+    type Context = Context<Self>;
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        self.end();
+    }
+}
+
+/// Sessions of the history requested by the ui
+#[derive(Debug, Clone, Message)]
+#[rtype(result = "()")]
+pub enum SessionQuery {
+    /// Overview of every stored session
+    List,
+    /// Session with all of its laps by its id
+    Open(String),
+    Remove(String),
+}
+
+impl Handler<SessionQuery> for SessionRecorder {
+    type Result = ();
+
+    fn handle(&mut self, msg: SessionQuery, _ctx: &mut Self::Context) -> Self::Result {
+        match msg {
+            SessionQuery::List => self.list(),
+            SessionQuery::Open(id) => match self.store.load(&id) {
+                Ok(session) => self.router.do_send(UiUpdate::Session(Some(session))),
+                Err(e) => {
+                    error!("failed to load session {id}: {e}");
+                    self.router.do_send(UiUpdate::Session(None));
+                }
+            },
+            SessionQuery::Remove(id) => {
+                // The session being recorded is still written to
+                if self.session.as_ref() == Some(&id) {
+                    return;
+                }
+                if let Err(e) = self.store.remove(&id) {
+                    error!("failed to remove session {id}: {e}");
+                }
+                self.list();
+            }
+        }
+    }
+}
+
+impl Handler<SessionEvent> for SessionRecorder {
+    type Result = ();
+
+    fn handle(&mut self, msg: SessionEvent, _ctx: &mut Self::Context) -> Self::Result {
+        match msg {
+            SessionEvent::Started(car, track, session_type) => self.start(car, track, session_type),
+            SessionEvent::Weather(weather) => {
+                self.weather = Some(weather);
+                self.record(SessionRecord::Weather {
+                    time: Local::now(),
+                    weather,
+                });
+            }
+            SessionEvent::Lap(lap) => {
+                self.laps += 1;
+                self.record(SessionRecord::Lap { lap });
+            }
+            SessionEvent::Wheels(wheels) => self.record(SessionRecord::Wheels { wheels }),
+            SessionEvent::Stint(stint) => self.record(SessionRecord::Stint { stint }),
+            SessionEvent::FuelPerLap(fuel_per_lap) => {
+                self.fuel_per_lap = Some(fuel_per_lap);
+                self.record(SessionRecord::Fuel {
+                    time: Local::now(),
+                    fuel_per_lap,
+                });
+            }
+            SessionEvent::Setup(name, setup) => {
+                if self.setups.get(&name) != Some(&setup) {
+                    self.setups.insert(name.clone(), setup.clone());
+                    self.record(SessionRecord::Setup {
+                        name,
+                        setup: Box::new(setup),
+                    });
+                }
+            }
+            SessionEvent::Ended => self.end(),
+        }
+    }
+}
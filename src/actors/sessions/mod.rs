@@ -0,0 +1,13 @@
+use std::path::PathBuf;
+
+#[cfg(feature = "app")]
+mod actor;
+mod store;
+#[cfg(feature = "app")]
+pub use actor::*;
+pub use store::*;
+
+/// Folder the sessions are stored in
+pub fn sessions_folder() -> PathBuf {
+    crate::data_folder().join("Sessions")
+}
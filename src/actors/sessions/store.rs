@@ -0,0 +1,467 @@
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{debug, error};
+
+use crate::{
+    actors::{
+        setup_manager::{is_plain_name, Setup},
+        ui::LapTimeData,
+        ui::Weather,
+    },
+    telemetry::{broadcast::LapType, shm::SessionType, stints::Stint, LapTime, LapWheels},
+};
+
+/// Extension of the session files, one json record per line
+const SESSION_EXTENSION: &str = "jsonl";
+/// Extension of the summary written next to a session once it ended
+const SUMMARY_EXTENSION: &str = "summary.json";
+
+#[derive(Debug, Error)]
+pub enum SessionError {
+    #[error("io error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("failed to parse session record: {0}")]
+    ParseError(#[from] serde_json::Error),
+    #[error("session {0} has no start")]
+    NotStarted(String),
+    #[error("invalid session id {0}")]
+    InvalidId(String),
+}
+
+/// Single change of a session, appended to its file as soon as it happens
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "record")]
+pub enum SessionRecord {
+    Started {
+        car: String,
+        track: String,
+        session_type: SessionType,
+        time: DateTime<Local>,
+    },
+    Weather {
+        time: DateTime<Local>,
+        weather: Weather,
+    },
+    Lap {
+        lap: LapTimeData,
+    },
+    Wheels {
+        wheels: LapWheels,
+    },
+    Stint {
+        stint: Stint,
+    },
+    Fuel {
+        time: DateTime<Local>,
+        fuel_per_lap: f32,
+    },
+    /// Adjusted setup the app wrote to ACC during the session by its file name,
+    /// ACC doesn't tell which of them is loaded
+    Setup {
+        name: String,
+        setup: Box<Setup>,
+    },
+    Ended {
+        time: DateTime<Local>,
+    },
+}
+
+/// Session put back together from its records
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    pub id: String,
+    pub car: String,
+    pub track: String,
+    pub session_type: SessionType,
+    pub started: DateTime<Local>,
+    /// `None` when the app closed before the session ended
+    pub ended: Option<DateTime<Local>>,
+    pub weather: Vec<(DateTime<Local>, Weather)>,
+    pub laps: BTreeMap<i32, LapTimeData>,
+    pub wheels: BTreeMap<i32, LapWheels>,
+    pub stints: Vec<Stint>,
+    pub fuel_per_lap: Vec<(DateTime<Local>, f32)>,
+    /// Latest version of every setup written during the session
+    pub setups: BTreeMap<String, Setup>,
+}
+
+/// Overview of a session to list and filter them without loading every lap
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub id: String,
+    pub car: String,
+    pub track: String,
+    pub session_type: SessionType,
+    pub started: DateTime<Local>,
+    pub laps: usize,
+    pub best_lap: Option<LapTime>,
+}
+
+/// Filter of the session history, fields left empty match every session
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SessionFilter {
+    pub car: Option<String>,
+    pub track: Option<String>,
+    pub session_type: Option<SessionType>,
+    /// First and last day the sessions started on
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl SessionFilter {
+    pub fn matches(&self, session: &SessionSummary) -> bool {
+        let day = session.started.date_naive();
+        self.car.as_ref().is_none_or(|car| *car == session.car)
+            && self.track.as_ref().is_none_or(|track| *track == session.track)
+            && self
+                .session_type
+                .is_none_or(|session_type| session_type == session.session_type)
+            && self.from.is_none_or(|from| day >= from)
+            && self.to.is_none_or(|to| day <= to)
+    }
+}
+
+impl Session {
+    fn replay(id: &str, records: impl IntoIterator<Item = SessionRecord>) -> Result<Self, SessionError> {
+        let mut records = records.into_iter();
+        let Some(SessionRecord::Started {
+            car,
+            track,
+            session_type,
+            time,
+        }) = records.next()
+        else {
+            return Err(SessionError::NotStarted(id.to_owned()));
+        };
+
+        let mut session = Session {
+            id: id.to_owned(),
+            car,
+            track,
+            session_type,
+            started: time,
+            ended: None,
+            weather: Vec::new(),
+            laps: BTreeMap::new(),
+            wheels: BTreeMap::new(),
+            stints: Vec::new(),
+            fuel_per_lap: Vec::new(),
+            setups: BTreeMap::new(),
+        };
+        for record in records {
+            match record {
+                SessionRecord::Started { .. } => {}
+                SessionRecord::Weather { time, weather } => session.weather.push((time, weather)),
+                SessionRecord::Lap { lap } => {
+                    session.laps.insert(lap.number, lap);
+                }
+                SessionRecord::Wheels { wheels } => {
+                    session.wheels.insert(wheels.number, wheels);
+                }
+                SessionRecord::Stint { stint } => session.stints.push(stint),
+                SessionRecord::Fuel { time, fuel_per_lap } => session.fuel_per_lap.push((time, fuel_per_lap)),
+                SessionRecord::Setup { name, setup } => {
+                    session.setups.insert(name, *setup);
+                }
+                SessionRecord::Ended { time } => session.ended = Some(time),
+            }
+        }
+
+        Ok(session)
+    }
+
+    pub fn summary(&self) -> SessionSummary {
+        SessionSummary {
+            id: self.id.clone(),
+            car: self.car.clone(),
+            track: self.track.clone(),
+            session_type: self.session_type,
+            started: self.started,
+            laps: self.laps.len(),
+            best_lap: self
+                .laps
+                .values()
+                .filter(|lap| lap.valid)
+                .map(|lap| lap.time.clone())
+                .min(),
+        }
+    }
+
+    /// Average of the valid laps driven at pace, in- and out-laps are left out
+    pub fn average_lap(&self) -> Option<LapTime> {
+        let laps: Vec<_> = self
+            .laps
+            .values()
+            .filter(|lap| lap.valid && lap.lap_type == LapType::Regular)
+            .map(|lap| lap.time.duration())
+            .collect();
+        if laps.is_empty() {
+            return None;
+        }
+
+        Some((laps.iter().sum::<Duration>() / laps.len() as u32).into())
+    }
+
+    /// Fuel usage per lap at the end of the session
+    pub fn fuel_per_lap(&self) -> Option<f32> {
+        self.fuel_per_lap.last().map(|(_, fuel_per_lap)| *fuel_per_lap)
+    }
+}
+
+/// Sessions on disk, every session is a file of records that is appended to while it is driven
+#[derive(Debug, Clone, Default)]
+pub struct SessionStore {
+    folder: PathBuf,
+}
+
+impl SessionStore {
+    pub fn new(folder: &Path) -> Self {
+        SessionStore {
+            folder: folder.to_owned(),
+        }
+    }
+
+    /// Id of a new session, sessions are sorted by their id
+    pub fn new_id(&self, started: &DateTime<Local>) -> String {
+        let id = started.format("%Y-%m-%d %H-%M-%S").to_string();
+
+        // Sessions can follow each other within a second, e.g. when skipping to the race
+        let mut unique = id.clone();
+        let mut count = 1;
+        while self.path(&unique).exists() {
+            count += 1;
+            unique = format!("{id} {count}");
+        }
+
+        unique
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.folder.join(format!("{id}.{SESSION_EXTENSION}"))
+    }
+
+    fn summary_path(&self, id: &str) -> PathBuf {
+        self.folder.join(format!("{id}.{SUMMARY_EXTENSION}"))
+    }
+
+    /// Ids come from the ui, they must not point outside of the session folder
+    fn check_id(id: &str) -> Result<(), SessionError> {
+        if is_plain_name(id) {
+            Ok(())
+        } else {
+            Err(SessionError::InvalidId(id.to_owned()))
+        }
+    }
+
+    /// Add a record to the end of the session
+    pub fn append(&self, id: &str, record: &SessionRecord) -> Result<(), SessionError> {
+        std::fs::create_dir_all(&self.folder)?;
+
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path(id))?
+            .write_all(line.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// End the session and write its summary, so listing the sessions doesn't replay all of them
+    pub fn end(&self, id: &str, time: DateTime<Local>) -> Result<(), SessionError> {
+        self.append(id, &SessionRecord::Ended { time })?;
+
+        let summary = self.load(id)?.summary();
+        std::fs::write(self.summary_path(id), serde_json::to_string_pretty(&summary)?)?;
+
+        Ok(())
+    }
+
+    /// Session by its id, a record cut off by a crash ends the session where it stopped
+    pub fn load(&self, id: &str) -> Result<Session, SessionError> {
+        Self::check_id(id)?;
+        let data = std::fs::read_to_string(self.path(id))?;
+        let records = data
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map_while(|line| {
+                serde_json::from_str::<SessionRecord>(line)
+                    .inspect_err(|e| error!("failed to parse record of session {id}: {e}"))
+                    .ok()
+            });
+
+        Session::replay(id, records)
+    }
+
+    /// Summary of an ended session, sessions without one are still recorded or were cut off by a crash
+    /// and are replayed instead
+    fn summary(&self, id: &str) -> Result<SessionSummary, SessionError> {
+        match std::fs::read_to_string(self.summary_path(id)) {
+            Ok(data) => Ok(serde_json::from_str(&data)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(self.load(id)?.summary()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Every stored session, newest first
+    pub fn sessions(&self) -> Vec<SessionSummary> {
+        let Ok(entries) = std::fs::read_dir(&self.folder) else {
+            return Vec::new();
+        };
+
+        let mut sessions: Vec<SessionSummary> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|e| e == SESSION_EXTENSION))
+            .filter_map(|path| {
+                let id = path.file_stem()?.to_string_lossy().into_owned();
+                self.summary(&id)
+                    .inspect_err(|e| error!("failed to load session {id}: {e}"))
+                    .ok()
+            })
+            .collect();
+        sessions.sort_by(|a, b| b.id.cmp(&a.id));
+
+        sessions
+    }
+
+    pub fn remove(&self, id: &str) -> Result<(), SessionError> {
+        Self::check_id(id)?;
+        debug!("removing session {id}");
+        std::fs::remove_file(self.path(id))?;
+        match std::fs::remove_file(self.summary_path(id)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn test_session_store() -> Result<(), SessionError> {
+    let folder = crate::TestFolder::new("sessions");
+    let store = SessionStore::new(&folder);
+
+    let started = Local::now();
+    let id = store.new_id(&started);
+    let lap = |number: i32, millis: u64, valid: bool| SessionRecord::Lap {
+        lap: LapTimeData {
+            number,
+            time: Duration::from_millis(millis).into(),
+            valid,
+            lap_type: if number == 1 {
+                LapType::Outlap
+            } else {
+                LapType::Regular
+            },
+            ..Default::default()
+        },
+    };
+    let records = [
+        SessionRecord::Started {
+            car: "bmw_m4_gt3".to_owned(),
+            track: "spa".to_owned(),
+            session_type: SessionType::Practice,
+            time: started,
+        },
+        SessionRecord::Weather {
+            time: started,
+            weather: Weather {
+                ambient_temp: 21,
+                track_temp: 28,
+                ..Default::default()
+            },
+        },
+        lap(1, 140_000, true),
+        lap(2, 138_500, true),
+        lap(3, 137_000, false),
+        SessionRecord::Wheels {
+            wheels: LapWheels {
+                number: 2,
+                ..Default::default()
+            },
+        },
+        SessionRecord::Stint {
+            stint: Stint {
+                first_lap: 1,
+                tyre_set: 2,
+                pit_stop: None,
+            },
+        },
+        SessionRecord::Fuel {
+            time: started,
+            fuel_per_lap: 3.1,
+        },
+        SessionRecord::Setup {
+            name: "21c 28c Race".to_owned(),
+            setup: Box::new(crate::actors::setup_manager::Setup::default()),
+        },
+    ];
+    for record in &records {
+        store.append(&id, record)?;
+    }
+
+    // Sessions are readable while they are still being written
+    let session = store.load(&id)?;
+    assert_eq!(session.track, "spa");
+    assert_eq!(session.ended, None);
+    assert_eq!(session.laps.len(), 3);
+    assert_eq!(session.weather[0].1.track_temp, 28);
+    assert!(session.wheels.contains_key(&2));
+    assert_eq!(session.stints[0].tyre_set, 2);
+    assert!(session.setups.contains_key("21c 28c Race"));
+    assert_eq!(session.average_lap(), Some(Duration::from_millis(138_500).into()));
+    assert_eq!(session.fuel_per_lap(), Some(3.1));
+
+    // Sessions that are still recorded are listed by replaying them
+    assert_eq!(store.sessions(), [session.summary()]);
+
+    store.end(&id, Local::now())?;
+    // A line cut off by a crash is ignored
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(store.path(&id))?
+        .write_all(b"{\"record\":\"Lap\",\"lap\":{")?;
+    assert!(store.load(&id)?.ended.is_some());
+    assert!(store.summary_path(&id).exists());
+
+    let summaries = store.sessions();
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].laps, 3);
+    assert_eq!(summaries[0].best_lap, Some(Duration::from_millis(138_500).into()));
+
+    assert!(matches!(store.load("../setups"), Err(SessionError::InvalidId(_))));
+    assert!(matches!(
+        store.remove("../setups"),
+        Err(SessionError::InvalidId(_))
+    ));
+
+    assert_ne!(store.new_id(&started), id);
+
+    let mut filter = SessionFilter {
+        car: Some("bmw_m4_gt3".to_owned()),
+        session_type: Some(SessionType::Practice),
+        from: Some(started.date_naive()),
+        ..Default::default()
+    };
+    assert!(filter.matches(&summaries[0]));
+    filter.track = Some("monza".to_owned());
+    assert!(!filter.matches(&summaries[0]));
+    filter.track = None;
+    filter.to = started.date_naive().pred_opt();
+    assert!(!filter.matches(&summaries[0]));
+
+    store.remove(&id)?;
+    assert!(store.sessions().is_empty());
+    assert!(!store.summary_path(&id).exists());
+
+    Ok(())
+}
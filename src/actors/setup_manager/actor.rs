@@ -0,0 +1,786 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use actix::prelude::*;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    actors::{
+        fuel_calculator::FuelMessage,
+        ui::{LapTimeData, TrackConditions, UiUpdate},
+        Router, SessionEvent,
+    },
+    telemetry::LapWheels,
+};
+
+use super::{
+    migrate_folder, migrate_library, read_templates, resolve_templates, validate, BatchEdit,
+    BrakeDuctRecommendation, BundlePreview, CarParameters, CarTrack, DesiredState, PressureBuildup,
+    PressureCorrection, Setup, SetupBundle, SetupChange, SetupCondition, SetupError, SetupField, SetupFile,
+    SetupHistory, SetupInfo, SetupJournal, SetupLibrary, SetupMeta, SetupPatch, SetupSheet, SetupType,
+    SetupVersion, VersionInfo,
+};
+
+#[derive(Debug)]
+#[allow(unused)]
+pub struct SetupManager {
+    pub router: Addr<Router>,
+
+    /// Car, track, weather, fuel and corrections the adjusted setups are derived from
+    pub state: DesiredState,
+    pub conditions: TrackConditions,
+    pub condition: SetupCondition,
+    pub meta: SetupMeta,
+
+    /// Laps of the current session used to compute the pressure correction
+    pub laps: Vec<LapWheels>,
+    pub pressure_correction: Option<PressureCorrection>,
+    /// Laps of the current stint used to learn the pressure build up
+    pub stint: Vec<LapWheels>,
+    /// Laps driven on the current brake ducts used to compute the brake duct recommendation
+    pub brake_laps: Vec<LapWheels>,
+    pub brake_ducts: Option<BrakeDuctRecommendation>,
+
+    pub templates: BTreeMap<String, SetupFile>,
+    pub setups: BTreeMap<String, SetupFile>,
+    /// Adjusted setups for the temperatures of the grid
+    pub grid: Vec<SetupFile>,
+    /// Paths of the adjusted setups currently written to the ACC setup folder
+    pub written: BTreeSet<PathBuf>,
+    /// Saved versions of every template, oldest first
+    pub history: BTreeMap<String, Vec<SetupVersion>>,
+
+    pub setup_folder: PathBuf,
+    pub template_folder: PathBuf,
+    pub bundle_folder: PathBuf,
+    pub sheet_folder: PathBuf,
+    /// Setups written to the ACC setup folder, to remove the ones left behind by a crash
+    pub journal: SetupJournal,
+
+    setup_scheduled: Option<SpawnHandle>,
+    /// Watches the template folder of the loaded track for changes made outside of the app
+    watcher: Option<RecommendedWatcher>,
+    reload_scheduled: Option<SpawnHandle>,
+}
+
+impl SetupManager {
+    pub fn new(router: Addr<Router>) -> SetupManager {
+        SetupManager {
+            router,
+
+            state: Default::default(),
+            conditions: Default::default(),
+            condition: Default::default(),
+            meta: Default::default(),
+
+            laps: Default::default(),
+            pressure_correction: None,
+            stint: Default::default(),
+            brake_laps: Default::default(),
+            brake_ducts: None,
+
+            templates: Default::default(),
+            setups: Default::default(),
+            grid: Default::default(),
+            written: Default::default(),
+            history: Default::default(),
+
+            setup_folder: Default::default(),
+            template_folder: Default::default(),
+            bundle_folder: Default::default(),
+            sheet_folder: Default::default(),
+            journal: Default::default(),
+
+            setup_scheduled: None,
+            watcher: None,
+            reload_scheduled: None,
+        }
+    }
+}
+
+impl Actor for SetupManager {
+    type Context = Context<Self>;
+
+    fn started(&mut self, _ctx: &mut Self::Context) {
+        self.setup_paths().unwrap();
+
+        let removed = self.journal.reconcile();
+        if removed > 0 {
+            info!("removed {removed} setups left behind by the last run");
+        }
+
+        let migrated = migrate_library(&self.template_folder);
+        if migrated > 0 {
+            info!("migrated {migrated} setup templates to metadata sidecars");
+        }
+        self.update_library();
+    }
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        debug!("setup manager stopped");
+        self.cleanup_setups();
+    }
+}
+
+impl Handler<SetupChange> for SetupManager {
+    type Result = ();
+
+    fn handle(&mut self, msg: SetupChange, ctx: &mut Self::Context) -> Self::Result {
+        // Adjusted setups are derived from the templates and the latest state as a whole,
+        // so it doesn't matter which of the changes arrives first
+        let mut changed = self.state.apply(&msg);
+
+        match msg {
+            SetupChange::Weather(_) => {
+                self.update_condition();
+            }
+            SetupChange::TrackConditions(conditions) => {
+                self.conditions = conditions;
+                self.update_condition();
+            }
+            SetupChange::Load(_, _) => match self.load() {
+                Ok(()) => self.watch_templates(ctx),
+                Err(err) => error!("failed to load setups: {err}"),
+            },
+            SetupChange::RaceFuel(_) => {
+                self.update_brake_ducts();
+            }
+            SetupChange::QualiFuel(_) | SetupChange::TelemetryLaps(_) | SetupChange::TemperatureGrid(_) => {}
+            SetupChange::LapWheels(lap) => {
+                changed |= self.add_stint_lap(&lap);
+                self.brake_laps.push(lap.clone());
+                self.laps.push(lap);
+                self.update_pressure_correction();
+                self.update_brake_ducts();
+            }
+            SetupChange::ApplyPressureCorrection(templates, author) => {
+                changed |= self.apply_pressure_correction(templates, &author);
+            }
+            SetupChange::ApplyBrakeDucts => {
+                changed |= self.apply_brake_ducts();
+            }
+            SetupChange::SaveTemplate(name, setup, info) => match self.save_template(&name, *setup, info) {
+                Ok(()) => changed = true,
+                Err(err) => error!("failed to save template {name}: {err}"),
+            },
+            SetupChange::SaveTemplateInfo(name, info) => match self.save_template_info(&name, info) {
+                Ok(()) => changed = true,
+                Err(err) => error!("failed to save info of template {name}: {err}"),
+            },
+            SetupChange::RestoreVersion(name, timestamp, info) => {
+                let version = self
+                    .history
+                    .get(&name)
+                    .and_then(|versions| versions.iter().find(|v| v.timestamp == timestamp))
+                    .cloned();
+
+                match version.map(|version| self.save_template(&name, version.setup, info)) {
+                    Some(Ok(())) => changed = true,
+                    Some(Err(err)) => error!("failed to restore template {name}: {err}"),
+                    None => error!("no version of template {name} from {timestamp}"),
+                }
+            }
+            SetupChange::RefreshLibrary => {
+                self.update_library();
+            }
+            SetupChange::ExportBundle(selection, author) => {
+                if let Err(err) = self.export_bundle(&selection, &author) {
+                    error!("failed to export setup bundle: {err}");
+                }
+            }
+            SetupChange::PreviewBundle(path) => {
+                let preview = path.and_then(|path| {
+                    self.preview_bundle(path)
+                        .inspect_err(|err| error!("failed to read setup bundle: {err}"))
+                        .ok()
+                });
+                self.router.do_send(UiUpdate::SetupBundle(preview));
+            }
+            SetupChange::ImportBundle(path, overwrite) => match self.import_bundle(&path, overwrite) {
+                Ok(imported) => changed |= imported,
+                Err(err) => error!("failed to import setup bundle: {err}"),
+            },
+            SetupChange::PreviewBatch(edit) => {
+                let preview = edit.and_then(|edit| {
+                    edit.preview(&self.template_folder)
+                        .inspect_err(|err| error!("failed to preview batch edit: {err}"))
+                        .ok()
+                });
+                self.router.do_send(UiUpdate::BatchPreview(preview));
+            }
+            SetupChange::ApplyBatch(edit, author) => match self.apply_batch(&edit, &author) {
+                Ok(applied) => changed |= applied,
+                Err(err) => error!("failed to apply batch edit: {err}"),
+            },
+            SetupChange::ExportSheet(setup, compare, format) => {
+                match SetupSheet::new(&setup, compare.as_deref()).save(&self.sheet_folder, format) {
+                    Ok(path) => self.router.do_send(UiUpdate::SetupSheet(path)),
+                    Err(err) => error!("failed to export setup sheet: {err}"),
+                }
+            }
+        };
+
+        if changed {
+            self.update_setups();
+            ctx.notify(CommitChanges::Schedule);
+        }
+    }
+}
+
+impl SetupManager {
+    fn setup_paths(&mut self) -> Result<(), SetupError> {
+        let documents = crate::documents_folder();
+
+        self.setup_folder = documents.clone();
+        self.setup_folder.push("Assetto Corsa Competizione");
+        self.setup_folder.push("Setups");
+
+        self.template_folder = crate::data_folder();
+        self.template_folder.push("SetupTemplates");
+
+        self.bundle_folder = crate::data_folder();
+        self.bundle_folder.push("Bundles");
+
+        self.sheet_folder = crate::data_folder();
+        self.sheet_folder.push("Sheets");
+
+        std::fs::create_dir_all(&self.template_folder)?;
+        std::fs::create_dir_all(&self.bundle_folder)?;
+
+        self.journal = SetupJournal::open(&crate::data_folder().join("setup_journal.json"));
+
+        Ok(())
+    }
+
+    /// Template folder of the loaded car and track
+    fn track_folder(&self) -> Option<PathBuf> {
+        let car_track = self.state.car_track.as_ref()?;
+        Some(self.template_folder.join(&car_track.car).join(&car_track.track))
+    }
+
+    /// Read the templates of the car and track of the state and start a new session on them
+    fn load(&mut self) -> Result<(), SetupError> {
+        let template_folder = self.track_folder().ok_or(SetupError::NoSetups)?;
+
+        self.laps.clear();
+        self.stint.clear();
+        self.brake_laps.clear();
+        self.update_pressure_correction();
+        self.update_brake_ducts();
+
+        // Whatever happens the templates of the previous track are no longer valid
+        self.templates.clear();
+        self.meta = SetupMeta::read(&template_folder);
+        debug!("loaded meta: {:?}", self.meta);
+
+        std::fs::create_dir_all(&template_folder)?;
+        migrate_folder(&template_folder)?;
+        self.templates = read_templates(&template_folder)?;
+
+        self.router
+            .do_send(UiUpdate::SetupTemplates(self.templates.clone()));
+        self.load_history(&template_folder);
+
+        // Send fuel per lap of whatever setup we get to fuelcalculator
+        // to at least have some value in it, preferring one for the current conditions
+        if let Some(setup) = self
+            .templates
+            .values()
+            .find(|setup| setup.condition == self.condition)
+            .or_else(|| self.templates.values().next())
+        {
+            self.router.do_send(FuelMessage::FuelPerLap(
+                setup.setup.basic_setup.strategy.fuel_per_lap,
+            ));
+        }
+
+        // Send the avg lap time from meta file to fuelcalculator
+        // to have a starting value to work with
+        self.router.do_send(FuelMessage::AvgLapTime(self.meta.avg_lap));
+        self.router
+            .do_send(UiUpdate::PersonalBest(self.meta.personal_best.clone()));
+
+        Ok(())
+    }
+
+    /// Derive the adjusted setups and the ones of the temperature grid from the templates
+    /// and the state again
+    fn update_setups(&mut self) {
+        self.setups = self
+            .state
+            .adjusted(&self.templates, &self.meta.pressure_buildup, &self.setup_folder);
+        self.grid = self
+            .state
+            .grid(&self.templates, &self.meta.pressure_buildup, &self.setup_folder);
+        self.router.do_send(UiUpdate::SetupAdjusted(self.setups.clone()));
+    }
+
+    /// Start watching the template folder of the loaded track, replacing the previous watcher
+    fn watch_templates(&mut self, ctx: &mut Context<Self>) {
+        let Some(folder) = self.track_folder() else {
+            return;
+        };
+        let manager = ctx.address();
+
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+            Ok(event) if !event.kind.is_access() => manager.do_send(ReloadTemplates::Schedule),
+            Ok(_) => {}
+            Err(e) => error!("template folder watch error: {e}"),
+        });
+        let watcher = watcher.and_then(|mut watcher| {
+            watcher.watch(&folder, RecursiveMode::NonRecursive)?;
+            Ok(watcher)
+        });
+
+        match watcher {
+            Ok(watcher) => {
+                debug!("watching template folder {:?}", folder);
+                self.watcher = Some(watcher);
+            }
+            Err(e) => {
+                error!("failed to watch template folder {:?}: {e}", folder);
+                self.watcher = None;
+            }
+        }
+    }
+
+    /// Read the templates of the loaded track again, returns true when any of them changed.
+    ///
+    /// Unlike a load this keeps the laps, corrections and offsets of the session.
+    fn reload_templates(&mut self) -> Result<bool, SetupError> {
+        let Some(template_folder) = self.track_folder() else {
+            return Ok(false);
+        };
+        migrate_folder(&template_folder)?;
+        let templates = read_templates(&template_folder)?;
+
+        let unchanged = |name: &String| match (self.templates.get(name), templates.get(name)) {
+            (Some(old), Some(new)) => old.setup == new.setup && old.info() == new.info(),
+            _ => false,
+        };
+        if templates.len() == self.templates.len() && templates.keys().all(unchanged) {
+            return Ok(false);
+        }
+        debug!("templates changed on disk, reloading");
+
+        self.templates = templates;
+        self.router
+            .do_send(UiUpdate::SetupTemplates(self.templates.clone()));
+        self.load_history(&template_folder);
+
+        Ok(true)
+    }
+
+    /// Select the setup condition matching the weather and track state
+    /// and let the ui know which setups to highlight
+    fn update_condition(&mut self) {
+        let weather = self.state.weather.unwrap_or_default();
+        let condition = SetupCondition::from_conditions(&weather, &self.conditions);
+        if condition != self.condition {
+            debug!("track condition changed to {condition:?}");
+            self.condition = condition;
+            self.router.do_send(UiUpdate::SetupCondition(condition));
+        }
+    }
+
+    fn update_pressure_correction(&mut self) {
+        let car = self
+            .state
+            .car_track
+            .as_ref()
+            .map(|car_track| car_track.car.as_str());
+        let pressure = &CarParameters::get(car.unwrap_or_default()).tyre_pressure;
+        self.pressure_correction = PressureCorrection::from_laps(&self.laps, pressure);
+        self.router
+            .do_send(UiUpdate::PressureCorrection(self.pressure_correction.clone()));
+    }
+
+    /// Apply the pressure correction computed from the measured hot pressures.
+    ///
+    /// When applied to the templates they are saved with a version by the author and the adjusted
+    /// setups pick it up from there, otherwise it is remembered as an offset on top of the weather
+    /// adjustment. Returns true when the correction was applied.
+    fn apply_pressure_correction(&mut self, templates: bool, author: &str) -> bool {
+        let Some(correction) = self.pressure_correction.take() else {
+            return false;
+        };
+        debug!("applying pressure correction: {correction:?}");
+
+        if templates {
+            // Every setup is corrected before saving, saving a base resolves its variants again
+            let corrected: Vec<(String, Setup)> = self
+                .templates
+                .iter()
+                .filter_map(|(name, template)| {
+                    let mut corrected = template.clone();
+                    corrected.adjust_pressure_clicks(correction.compound, &correction.clicks);
+                    (corrected.setup != template.setup).then(|| (name.clone(), corrected.setup))
+                })
+                .collect();
+            for (name, setup) in corrected {
+                let info = VersionInfo {
+                    author: author.to_owned(),
+                    note: "Pressure correction".to_owned(),
+                };
+                if let Err(e) = self.save_template(&name, setup, info) {
+                    error!("failed to save template {name}: {e}");
+                }
+            }
+        } else {
+            self.state
+                .add_pressure_offset(correction.compound, &correction.clicks);
+        }
+
+        // Laps driven so far were on the old pressures
+        self.laps.clear();
+        self.update_pressure_correction();
+
+        true
+    }
+
+    /// Collect the laps of the current stint and add them to the pressure build up model
+    /// once there are enough, returns true when the model changed
+    fn add_stint_lap(&mut self, lap: &LapWheels) -> bool {
+        if lap.stint_lap <= 1 {
+            self.stint.clear();
+        }
+        if lap.stint_lap == 0 || lap.stint_lap as usize != self.stint.len() + 1 {
+            return false;
+        }
+
+        self.stint.push(lap.clone());
+        if self.stint.len() != PressureBuildup::STINT_LAPS {
+            return false;
+        }
+
+        debug!("adding stint to pressure build up");
+        self.meta.pressure_buildup.add_stint(&self.stint);
+        self.save_meta();
+
+        true
+    }
+
+    /// Add a completed lap to the personal best of the car and track
+    fn add_lap(&mut self, lap: &LapTimeData) {
+        if self.track_folder().is_none() || !self.meta.personal_best.add_lap(lap) {
+            return;
+        }
+
+        self.save_meta();
+        self.router
+            .do_send(UiUpdate::PersonalBest(self.meta.personal_best.clone()));
+    }
+
+    fn save_meta(&self) {
+        let Some(folder) = self.track_folder() else {
+            return;
+        };
+        if let Err(e) = self.meta.save(&folder) {
+            error!("failed to save meta to {folder:?}: {e}");
+        }
+    }
+
+    fn update_brake_ducts(&mut self) {
+        self.brake_ducts = BrakeDuctRecommendation::from_laps(&self.brake_laps, self.race_laps());
+        self.router
+            .do_send(UiUpdate::BrakeDucts(self.brake_ducts.clone()));
+    }
+
+    /// Estimated amount of laps in the race from the race fuel and the fuel usage of the race setups
+    fn race_laps(&self) -> f32 {
+        let race_fuel = self.state.race_fuel.unwrap_or_default();
+        self.templates
+            .values()
+            .find(|setup| setup.setup_type == SetupType::Race)
+            .map(|setup| setup.setup.basic_setup.strategy.fuel_per_lap)
+            .filter(|fuel_per_lap| *fuel_per_lap > 0.0)
+            .map(|fuel_per_lap| race_fuel as f32 / fuel_per_lap)
+            .unwrap_or_default()
+    }
+
+    /// Apply the brake duct recommendation to the adjusted race setups until the next load,
+    /// returns true when the recommendation was applied
+    fn apply_brake_ducts(&mut self) -> bool {
+        let Some(recommendation) = self.brake_ducts.take() else {
+            return false;
+        };
+        debug!("applying brake duct recommendation: {recommendation:?}");
+
+        self.state.add_brake_ducts(&recommendation.clicks);
+
+        // Laps driven so far were on the old brake ducts
+        self.brake_laps.clear();
+        self.update_brake_ducts();
+
+        true
+    }
+
+    /// Snapshot templates that changed outside of the app and let the ui know about all versions
+    fn load_history(&mut self, template_folder: &Path) {
+        self.history = self
+            .templates
+            .iter()
+            .map(|(name, template)| {
+                let history = SetupHistory::new(template_folder, name);
+                if let Err(e) = history.track(&template.setup) {
+                    error!("failed to snapshot template {name}: {e}");
+                }
+
+                (name.clone(), history.versions())
+            })
+            .collect();
+
+        self.router.do_send(UiUpdate::SetupHistory(self.history.clone()));
+    }
+
+    /// Save an edited template, its adjusted copy is derived from it again afterwards.
+    ///
+    /// Templates based on another one only store their changes to the base,
+    /// and the templates based on this one pick up the changes.
+    fn save_template(&mut self, name: &str, setup: Setup, info: VersionInfo) -> Result<(), SetupError> {
+        for warning in validate(&setup).iter().filter_map(SetupField::warning) {
+            warn!("saving template {name} with {warning}");
+        }
+
+        let patch = self.rebuild_patch(name, &setup)?;
+        let template = self.templates.get_mut(name).ok_or(SetupError::NoSetups)?;
+        template.setup = setup;
+        template.patch = patch;
+        template.save()?;
+
+        let version = SetupVersion::new(template.setup.clone(), info);
+        SetupHistory::new(&template.path, name).snapshot(&version)?;
+        self.history.entry(name.to_owned()).or_default().push(version);
+        resolve_templates(&mut self.templates);
+        self.router.do_send(UiUpdate::SetupHistory(self.history.clone()));
+        self.router
+            .do_send(UiUpdate::SetupTemplates(self.templates.clone()));
+
+        Ok(())
+    }
+
+    /// Changes of a variant to its base for the setup, None for templates without a base
+    fn rebuild_patch(&self, name: &str, setup: &Setup) -> Result<Option<SetupPatch>, SetupError> {
+        match self.templates.get(name).and_then(|t| t.patch.as_ref()) {
+            Some(patch) => {
+                let base = self.templates.get(&patch.base).ok_or(SetupError::NoSetups)?;
+                Ok(Some(SetupPatch::new(&patch.base, &base.setup, setup)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Replace the metadata of a template, the adjusted copy starts over from the new temperatures
+    fn save_template_info(&mut self, name: &str, info: SetupInfo) -> Result<(), SetupError> {
+        let template = self.templates.get_mut(name).ok_or(SetupError::NoSetups)?;
+        template.set_info(info);
+        template.save()?;
+
+        self.router
+            .do_send(UiUpdate::SetupTemplates(self.templates.clone()));
+
+        Ok(())
+    }
+
+    fn update_library(&self) {
+        let library = SetupLibrary::read(&self.template_folder, &self.bundle_folder);
+        self.router.do_send(UiUpdate::SetupLibrary(library));
+    }
+
+    fn export_bundle(&self, selection: &[CarTrack], author: &str) -> Result<(), SetupError> {
+        let bundle = SetupBundle::export(&self.template_folder, selection, author)?;
+        bundle.save(&self.bundle_folder.join(bundle.file_name()))?;
+        self.update_library();
+
+        Ok(())
+    }
+
+    fn preview_bundle(&self, path: PathBuf) -> Result<BundlePreview, SetupError> {
+        let bundle = SetupBundle::read(&path)?;
+
+        Ok(BundlePreview {
+            path,
+            conflicts: bundle.conflicts(&self.template_folder),
+            manifest: bundle.manifest,
+        })
+    }
+
+    /// Import a bundle and reload the templates if the current track was part of it,
+    /// returns true when the templates changed
+    fn import_bundle(&mut self, path: &Path, overwrite: bool) -> Result<bool, SetupError> {
+        let bundle = SetupBundle::read(path)?;
+        let imported = bundle.import(&self.template_folder, overwrite)?;
+
+        self.router.do_send(UiUpdate::SetupBundle(None));
+        self.update_library();
+
+        match &self.state.car_track {
+            Some(current) if imported.contains(current) => self.reload_templates(),
+            _ => Ok(false),
+        }
+    }
+
+    /// Apply a batch edit and reload the templates if it changed the current track,
+    /// returns true when the templates changed
+    fn apply_batch(&mut self, edit: &BatchEdit, author: &str) -> Result<bool, SetupError> {
+        let changed = edit.apply(&self.template_folder, author)?;
+
+        self.router.do_send(UiUpdate::BatchPreview(None));
+        self.update_library();
+
+        match &self.state.car_track {
+            Some(current) if changed.contains(current) => self.reload_templates(),
+            _ => Ok(false),
+        }
+    }
+
+    /// Write the adjusted setups and the ones of the temperature grid to the ACC setup folder,
+    /// removing the ones written before that are no longer part of them,
+    /// e.g. because their temperatures changed or the grid was turned off
+    fn save_setups(&mut self) {
+        // The live setup replaces the grid setup of the same temperatures
+        let setups: BTreeMap<PathBuf, &SetupFile> = self
+            .grid
+            .iter()
+            .chain(self.setups.values())
+            .map(|setup| (setup.path_with_name(), setup))
+            .collect();
+        let paths: BTreeSet<PathBuf> = setups.keys().cloned().collect();
+        let contents: Vec<(&PathBuf, &SetupFile, String)> = setups
+            .iter()
+            .filter_map(|(path, setup)| match serde_json::to_string_pretty(&setup.setup) {
+                Ok(contents) => Some((path, *setup, contents)),
+                Err(e) => {
+                    error!("failed to save setup {}: {e:?}", setup.name);
+                    None
+                }
+            })
+            .collect();
+
+        // Every setup is in the journal on disk before it is written
+        contents
+            .iter()
+            .for_each(|(path, _, contents)| self.journal.record(path, contents));
+        if let Err(e) = self.journal.persist() {
+            error!("failed to save setup journal, not saving setups: {e:?}");
+            return;
+        }
+
+        self.written
+            .difference(&paths)
+            .for_each(|path| match self.journal.remove(path) {
+                Ok(_) => {}
+                Err(e) => error!("failed to delete setup: {e:?}"),
+            });
+        contents.iter().for_each(|(path, setup, contents)| {
+            if let Err(e) = self.journal.write(path, contents) {
+                error!("failed to save setup {}: {e:?}", setup.name);
+            }
+        });
+        self.written = paths;
+        if let Err(e) = self.journal.persist() {
+            error!("failed to save setup journal: {e:?}");
+        }
+
+        // Only the live setups are recorded with the session, the grid is for other weather
+        for setup in self.setups.values() {
+            let path = setup.path_with_name();
+            let name = path.file_stem().unwrap_or_default().to_string_lossy();
+            self.router
+                .do_send(SessionEvent::Setup(name.into_owned(), setup.setup.clone()));
+        }
+    }
+
+    fn cleanup_setups(&mut self) {
+        self.written.iter().for_each(|path| {
+            self.journal.remove(path).unwrap_or_else(|e| {
+                error!("failed to delete setup: {e:?}");
+                false
+            });
+        });
+        self.written.clear();
+        if let Err(e) = self.journal.persist() {
+            error!("failed to save setup journal: {e:?}");
+        }
+    }
+}
+
+impl Handler<SessionEvent> for SetupManager {
+    type Result = ();
+
+    fn handle(&mut self, msg: SessionEvent, _ctx: &mut Self::Context) -> Self::Result {
+        if let SessionEvent::Lap(lap) = msg {
+            self.add_lap(&lap);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Message)]
+#[rtype(result = "()")]
+enum CommitChanges {
+    Schedule,
+    Handle,
+}
+
+impl Handler<CommitChanges> for SetupManager {
+    type Result = ();
+
+    fn handle(&mut self, msg: CommitChanges, ctx: &mut Self::Context) -> Self::Result {
+        match msg {
+            CommitChanges::Schedule => {
+                if let Some(handle) = self.setup_scheduled {
+                    ctx.cancel_future(handle);
+                }
+
+                self.setup_scheduled =
+                    Some(ctx.notify_later(CommitChanges::Handle, Duration::from_millis(500)));
+            }
+            CommitChanges::Handle => {
+                self.setup_scheduled = None;
+                self.save_setups();
+                self.router.do_send(UiUpdate::SetupAdjusted(self.setups.clone()));
+            }
+        }
+    }
+}
+
+/// Reload the templates shortly after the folder changed, so saves that touch a file
+/// several times only cause a single reload
+#[derive(Debug, Clone, Message)]
+#[rtype(result = "()")]
+enum ReloadTemplates {
+    Schedule,
+    Handle,
+}
+
+impl Handler<ReloadTemplates> for SetupManager {
+    type Result = ();
+
+    fn handle(&mut self, msg: ReloadTemplates, ctx: &mut Self::Context) -> Self::Result {
+        match msg {
+            ReloadTemplates::Schedule => {
+                if let Some(handle) = self.reload_scheduled {
+                    ctx.cancel_future(handle);
+                }
+
+                self.reload_scheduled =
+                    Some(ctx.notify_later(ReloadTemplates::Handle, Duration::from_millis(500)));
+            }
+            ReloadTemplates::Handle => {
+                self.reload_scheduled = None;
+                match self.reload_templates() {
+                    Ok(true) => {
+                        self.update_setups();
+                        ctx.notify(CommitChanges::Schedule);
+                    }
+                    Ok(false) => {}
+                    Err(err) => error!("failed to reload templates: {err}"),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,383 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use serde_json::Value;
+use tracing::{debug, error};
+
+use super::{
+    diff, fields, read_templates, set_field, CarTrack, FieldChange, Setup, SetupError, SetupFile,
+    SetupHistory, SetupPatch, SetupVersion, VersionInfo,
+};
+
+/// Extension of the files a batch edit is staged in before they replace the templates
+const STAGING_EXTENSION: &str = "json.batch";
+
+/// How a batch edit changes the value of a field
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BatchOperation {
+    /// Add an amount of clicks to the value of every template
+    Add(i64),
+    /// Set the value of every template
+    Set(i64),
+}
+
+impl BatchOperation {
+    fn apply(&self, value: &Value) -> Option<Value> {
+        let clicks = value.as_i64()?;
+        Some(match self {
+            BatchOperation::Add(change) => (clicks + change).into(),
+            BatchOperation::Set(value) => (*value).into(),
+        })
+    }
+}
+
+impl Display for BatchOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatchOperation::Add(change) => write!(f, "{change:+}"),
+            BatchOperation::Set(value) => write!(f, "= {value}"),
+        }
+    }
+}
+
+/// Template of a car and track in the template folder
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TemplateRef {
+    pub car_track: CarTrack,
+    pub name: String,
+}
+
+/// A single change applied to a selection of templates, e.g. TC1 +1 or telemetry laps = 0
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchEdit {
+    /// Path of the field in the setup json, e.g. `basicSetup.electronics.tC1`
+    pub path: String,
+    pub operation: BatchOperation,
+    pub templates: Vec<TemplateRef>,
+}
+
+/// Changes a batch edit makes to one of its templates
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchChange {
+    pub template: TemplateRef,
+    pub changes: Vec<FieldChange>,
+    /// Reason the edit can't be applied to the template
+    pub error: Option<String>,
+    /// Why the edited value looks wrong for the car, the edit is still applied
+    pub warning: Option<String>,
+}
+
+/// Changes of a batch edit, computed without writing anything
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchPreview {
+    pub edit: BatchEdit,
+    pub changes: Vec<BatchChange>,
+}
+
+impl BatchPreview {
+    /// The edit only goes through if it can be applied to every template
+    pub fn is_valid(&self) -> bool {
+        self.changes.iter().all(|change| change.error.is_none())
+    }
+}
+
+impl BatchEdit {
+    pub fn preview(&self, template_folder: &Path) -> Result<BatchPreview, SetupError> {
+        let tracks = self.read(template_folder)?;
+
+        let changes = self
+            .templates
+            .iter()
+            .map(|template| {
+                let file = tracks
+                    .get(&template.car_track)
+                    .and_then(|templates| templates.get(&template.name));
+                let (changes, error, warning) = match file.map(|file| (file, self.edit(&file.setup))) {
+                    Some((file, Ok(setup))) => (diff(&file.setup, &setup), None, self.warning(&setup)),
+                    Some((_, Err(error))) => (Vec::new(), Some(error), None),
+                    None => (Vec::new(), Some("template not found".to_owned()), None),
+                };
+
+                BatchChange {
+                    template: template.clone(),
+                    changes,
+                    error,
+                    warning,
+                }
+            })
+            .collect();
+
+        Ok(BatchPreview {
+            edit: self.clone(),
+            changes,
+        })
+    }
+
+    /// Apply the edit to every template and add a version to their history, returns the tracks
+    /// that changed. Nothing is written unless the edit is valid for all of the templates.
+    pub fn apply(&self, template_folder: &Path, author: &str) -> Result<Vec<CarTrack>, SetupError> {
+        let mut tracks = self.read(template_folder)?;
+
+        let mut edited = Vec::new();
+        for template in &self.templates {
+            let file = tracks
+                .get(&template.car_track)
+                .and_then(|templates| templates.get(&template.name))
+                .ok_or(SetupError::NoSetups)?;
+            let setup = self
+                .edit(&file.setup)
+                .map_err(|e| SetupError::InvalidSetup(format!("{}: {e}", template.name)))?;
+            edited.push((template, setup));
+        }
+        for (template, setup) in &edited {
+            if let Some(file) = tracks
+                .get_mut(&template.car_track)
+                .and_then(|templates| templates.get_mut(&template.name))
+            {
+                file.setup = setup.clone();
+            }
+        }
+
+        // Variants keep storing their changes to the base, which may be part of the edit as well
+        let mut files: Vec<SetupFile> = Vec::new();
+        for (template, _) in &edited {
+            let templates = &tracks[&template.car_track];
+            let mut file = templates[&template.name].clone();
+            if let Some(patch) = &file.patch {
+                let base = templates.get(&patch.base).ok_or(SetupError::NoSetups)?;
+                file.patch = Some(SetupPatch::new(&patch.base, &base.setup, &file.setup)?);
+            }
+            files.push(file);
+        }
+
+        // Stage every file first, so a failing write leaves all of the templates untouched
+        let mut staged: Vec<(PathBuf, PathBuf)> = Vec::new();
+        for file in &files {
+            let path = file.path_with_name();
+            let staging = path.with_extension(STAGING_EXTENSION);
+            let result = file
+                .contents()
+                .and_then(|contents| std::fs::write(&staging, contents).map_err(SetupError::from));
+            if let Err(e) = result {
+                let _ = std::fs::remove_file(&staging);
+                roll_back(&staged, &[]);
+                return Err(e);
+            }
+            staged.push((staging, path));
+        }
+
+        // Versions are written before the templates change, and removed again if they don't
+        let mut versions: Vec<(SetupHistory, SetupVersion)> = Vec::new();
+        for file in &files {
+            let info = VersionInfo {
+                author: author.to_owned(),
+                note: format!("Batch edit {} {}", self.label(&file.setup), self.operation),
+            };
+            let history = SetupHistory::new(&file.path, &file.name);
+            let version = SetupVersion::new(file.setup.clone(), info);
+            if let Err(e) = history.snapshot(&version) {
+                roll_back(&staged, &versions);
+                return Err(e);
+            }
+            versions.push((history, version));
+        }
+
+        // The replaced templates are kept to put them back if one of the renames fails
+        let mut replaced: Vec<(&Path, Vec<u8>)> = Vec::new();
+        for (staging, path) in &staged {
+            debug!("saving batch edited setup to {:?}", path);
+            let result = std::fs::read(path).and_then(|original| {
+                std::fs::rename(staging, path)?;
+                Ok(original)
+            });
+            match result {
+                Ok(original) => replaced.push((path, original)),
+                Err(e) => {
+                    replaced.iter().for_each(|(path, original)| {
+                        if let Err(e) = std::fs::write(path, original) {
+                            error!("failed to restore {:?} after a failed batch edit: {e}", path);
+                        }
+                    });
+                    roll_back(&staged, &versions);
+                    return Err(e.into());
+                }
+            }
+        }
+
+        let mut changed: Vec<CarTrack> = Vec::new();
+        for template in &self.templates {
+            if !changed.contains(&template.car_track) {
+                changed.push(template.car_track.clone());
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// Readable name of the edited field, e.g. `Brake Duct Front`
+    pub fn label(&self, setup: &Setup) -> String {
+        fields(setup)
+            .into_iter()
+            .find(|field| field.path == self.path)
+            .map(|field| field.label)
+            .unwrap_or_else(|| self.path.clone())
+    }
+
+    /// Templates of every track in the selection, with variants resolved
+    fn read(
+        &self,
+        template_folder: &Path,
+    ) -> Result<BTreeMap<CarTrack, BTreeMap<String, SetupFile>>, SetupError> {
+        let mut tracks = BTreeMap::new();
+        for template in &self.templates {
+            if tracks.contains_key(&template.car_track) {
+                continue;
+            }
+
+            let folder = template_folder
+                .join(&template.car_track.car)
+                .join(&template.car_track.track);
+            tracks.insert(template.car_track.clone(), read_templates(&folder)?);
+        }
+
+        Ok(tracks)
+    }
+
+    /// Warning of the edited field, values that were out of range before are kept without one
+    fn warning(&self, setup: &Setup) -> Option<String> {
+        fields(setup)
+            .into_iter()
+            .find(|field| field.path == self.path)
+            .and_then(|field| field.warning())
+    }
+
+    /// Setup with the edit applied, or why it can't be
+    fn edit(&self, template: &Setup) -> Result<Setup, String> {
+        let field = fields(template)
+            .into_iter()
+            .find(|field| field.path == self.path)
+            .ok_or_else(|| format!("setup has no field {}", self.path))?;
+        let value = self
+            .operation
+            .apply(&field.value)
+            .ok_or_else(|| format!("{} is not a value in clicks", field.label))?;
+
+        let mut setup = template.clone();
+        set_field(&mut setup, &self.path, value).map_err(|e| e.to_string())?;
+        Ok(setup)
+    }
+}
+
+/// Remove the staging files and the versions left over after a failed batch edit
+fn roll_back(staged: &[(PathBuf, PathBuf)], versions: &[(SetupHistory, SetupVersion)]) {
+    staged.iter().for_each(|(staging, _)| {
+        let _ = std::fs::remove_file(staging);
+    });
+    versions.iter().for_each(|(history, version)| {
+        if let Err(e) = history.remove(version) {
+            error!("failed to remove a version of a failed batch edit: {e}");
+        }
+    });
+}
+
+#[test]
+fn test_batch_edit() -> Result<(), SetupError> {
+    let folder = crate::TestFolder::new("batch");
+
+    let setup = super::setup::setup();
+    let tc1 = setup.basic_setup.electronics.tc1;
+    let mut templates = Vec::new();
+    for track in ["monza", "spa"] {
+        let track_folder = folder.join("car").join(track);
+        std::fs::create_dir_all(&track_folder)?;
+        std::fs::write(track_folder.join("Race.json"), serde_json::to_string(&setup)?)?;
+        templates.push(TemplateRef {
+            car_track: CarTrack {
+                car: "car".to_owned(),
+                track: track.to_owned(),
+            },
+            name: "Race".to_owned(),
+        });
+    }
+    std::fs::write(
+        folder.join("car").join("spa").join("Race Wet.json"),
+        r#"{ "base": "Race", "patch": { "basicSetup": { "electronics": { "tC1": 9 } } } }"#,
+    )?;
+    templates.push(TemplateRef {
+        car_track: templates[1].car_track.clone(),
+        name: "Race Wet".to_owned(),
+    });
+
+    let edit = BatchEdit {
+        path: "basicSetup.electronics.tC1".to_owned(),
+        operation: BatchOperation::Add(1),
+        templates: templates.clone(),
+    };
+    let preview = edit.preview(&folder)?;
+    assert!(preview.is_valid());
+    assert_eq!(preview.changes[0].changes[0].clicks(), Some(1));
+    assert_eq!(preview.changes[2].changes[0].to, Some(10.into()));
+
+    assert_eq!(edit.apply(&folder, "Author")?.len(), 2);
+    let spa = read_templates(&folder.join("car").join("spa"))?;
+    assert_eq!(spa["Race"].setup.basic_setup.electronics.tc1, tc1 + 1);
+    assert_eq!(spa["Race Wet"].setup.basic_setup.electronics.tc1, 10);
+
+    let versions = SetupHistory::new(&folder.join("car").join("monza"), "Race").versions();
+    assert_eq!(versions.len(), 1);
+    assert_eq!(versions[0].info.note, "Batch edit TC1 +1");
+
+    // Values out of the range of the car are only a warning
+    let edit = BatchEdit {
+        path: "advancedSetup.aeroBalance.brakeDuct[0]".to_owned(),
+        operation: BatchOperation::Set(7),
+        templates: templates.clone(),
+    };
+    let preview = edit.preview(&folder)?;
+    assert!(preview.is_valid());
+    assert_eq!(
+        preview.changes[0].warning.as_deref(),
+        Some("Brake Duct Front is outside of 0 to 6")
+    );
+
+    // An edit that is invalid for one template leaves all of them untouched
+    templates.push(TemplateRef {
+        car_track: templates[0].car_track.clone(),
+        name: "Missing".to_owned(),
+    });
+    let edit = BatchEdit {
+        path: "basicSetup.electronics.tC1".to_owned(),
+        operation: BatchOperation::Set(0),
+        templates: templates.clone(),
+    };
+    assert!(!edit.preview(&folder)?.is_valid());
+    assert!(matches!(edit.apply(&folder, "Author"), Err(SetupError::NoSetups)));
+    let monza = read_templates(&folder.join("car").join("monza"))?;
+    assert_eq!(monza["Race"].setup.basic_setup.electronics.tc1, tc1 + 1);
+
+    // Listing a template twice fails its second rename after the first one replaced it,
+    // which restores the template and removes the versions of the edit
+    let edit = BatchEdit {
+        path: "basicSetup.electronics.tC1".to_owned(),
+        operation: BatchOperation::Set(0),
+        templates: vec![templates[0].clone(), templates[0].clone()],
+    };
+    assert!(matches!(
+        edit.apply(&folder, "Author"),
+        Err(SetupError::IoError(_))
+    ));
+    let monza = read_templates(&folder.join("car").join("monza"))?;
+    assert_eq!(monza["Race"].setup.basic_setup.electronics.tc1, tc1 + 1);
+    assert_eq!(
+        SetupHistory::new(&folder.join("car").join("monza"), "Race")
+            .versions()
+            .len(),
+        1
+    );
+    assert!(!folder.join("car").join("monza").join("Race.json.batch").exists());
+
+    Ok(())
+}
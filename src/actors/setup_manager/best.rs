@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actors::ui::LapTimeData,
+    telemetry::{broadcast::LapType, LapTime},
+};
+
+/// Latest laps the rolling average and consistency are computed over
+const ROLLING_LAPS: usize = 20;
+
+/// Fastest lap and sectors of a car on a track with the pace of the latest laps.
+///
+/// Only valid laps driven at pace count, in- and out-laps are left out.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersonalBest {
+    /// Fastest lap with its sectors
+    pub lap: Option<LapTimeData>,
+    /// Fastest time of every sector, possibly from different laps
+    pub sectors: Vec<LapTime>,
+    /// Latest lap times, oldest first
+    pub recent: Vec<LapTime>,
+}
+
+impl PersonalBest {
+    /// Best of the laps, e.g. to get the session best
+    pub fn from_laps<'a>(laps: impl IntoIterator<Item = &'a LapTimeData>) -> Self {
+        let mut best = PersonalBest::default();
+        for lap in laps {
+            best.add_lap(lap);
+        }
+        best
+    }
+
+    /// Add a completed lap, returns false when the lap doesn't count
+    pub fn add_lap(&mut self, lap: &LapTimeData) -> bool {
+        if !lap.valid || lap.lap_type != LapType::Regular || lap.time.duration().is_zero() {
+            return false;
+        }
+
+        if self.lap.as_ref().is_none_or(|best| lap.time < best.time) {
+            self.lap = Some(lap.clone());
+        }
+
+        // Sectors of a lap cut short by a reset can't be compared
+        if self.sectors.is_empty() {
+            self.sectors = lap.sectors.clone();
+        } else if self.sectors.len() == lap.sectors.len() {
+            for (best, sector) in self.sectors.iter_mut().zip(&lap.sectors) {
+                if sector < best {
+                    *best = sector.clone();
+                }
+            }
+        }
+
+        self.recent.push(lap.time.clone());
+        if self.recent.len() > ROLLING_LAPS {
+            self.recent.remove(0);
+        }
+
+        true
+    }
+
+    /// Lap made of the best sectors
+    pub fn theoretical_best(&self) -> Option<LapTime> {
+        if self.sectors.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.sectors
+                .iter()
+                .map(|sector| sector.duration())
+                .sum::<Duration>()
+                .into(),
+        )
+    }
+
+    /// Average of the latest laps
+    pub fn average(&self) -> Option<LapTime> {
+        if self.recent.is_empty() {
+            return None;
+        }
+
+        let sum: Duration = self.recent.iter().map(|lap| lap.duration()).sum();
+        Some((sum / self.recent.len() as u32).into())
+    }
+
+    /// Standard deviation of the latest laps, lower is more consistent
+    pub fn consistency(&self) -> Option<Duration> {
+        let average = self.average()?.duration().as_secs_f64();
+        let variance = self
+            .recent
+            .iter()
+            .map(|lap| (lap.duration().as_secs_f64() - average).powi(2))
+            .sum::<f64>()
+            / self.recent.len() as f64;
+
+        Some(Duration::from_secs_f64(variance.sqrt()))
+    }
+
+    /// Lap time is the fastest one
+    pub fn is_best_lap(&self, time: &LapTime) -> bool {
+        self.lap.as_ref().is_some_and(|best| best.time == *time)
+    }
+
+    /// Sector time is the fastest one of the sector
+    pub fn is_best_sector(&self, sector: usize, time: &LapTime) -> bool {
+        self.sectors.get(sector).is_some_and(|best| best == time)
+    }
+}
+
+#[test]
+fn test_personal_best() {
+    let lap = |number: i32, sectors: [u64; 3], valid: bool, lap_type: LapType| LapTimeData {
+        number,
+        sectors: sectors
+            .iter()
+            .map(|millis| Duration::from_millis(*millis).into())
+            .collect(),
+        time: Duration::from_millis(sectors.iter().sum()).into(),
+        valid,
+        lap_type,
+    };
+
+    let mut best = PersonalBest::default();
+    assert_eq!(best.theoretical_best(), None);
+    assert_eq!(best.consistency(), None);
+
+    assert!(!best.add_lap(&lap(1, [40_000, 50_000, 40_000], true, LapType::Outlap)));
+    assert!(best.add_lap(&lap(2, [30_000, 50_000, 30_000], true, LapType::Regular)));
+    assert!(best.add_lap(&lap(3, [31_000, 48_000, 31_000], true, LapType::Regular)));
+    assert!(!best.add_lap(&lap(4, [29_000, 40_000, 29_000], false, LapType::Regular)));
+
+    assert_eq!(best.lap.as_ref().map(|lap| lap.number), Some(2));
+    assert!(best.is_best_lap(&Duration::from_millis(110_000).into()));
+    assert!(best.is_best_sector(1, &Duration::from_millis(48_000).into()));
+    assert!(!best.is_best_sector(1, &Duration::from_millis(50_000).into()));
+    assert_eq!(
+        best.theoretical_best(),
+        Some(Duration::from_millis(108_000).into())
+    );
+    assert_eq!(best.average(), Some(Duration::from_millis(110_000).into()));
+    assert_eq!(best.consistency(), Some(Duration::ZERO));
+
+    for number in 5..30 {
+        best.add_lap(&lap(number, [30_000, 50_000, 32_000], true, LapType::Regular));
+    }
+    assert_eq!(best.recent.len(), ROLLING_LAPS);
+    assert_eq!(best.average(), Some(Duration::from_millis(112_000).into()));
+
+    let session = PersonalBest::from_laps(&[lap(1, [31_000, 49_000, 30_500], true, LapType::Regular)]);
+    assert!(session.is_best_sector(2, &Duration::from_millis(30_500).into()));
+}
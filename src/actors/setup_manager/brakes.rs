@@ -0,0 +1,216 @@
+use crate::telemetry::{shm::Wheels, LapWheels};
+
+use super::LinearFit;
+
+/// Amount of most recent laps the brake temperatures are averaged over
+const BRAKE_LAPS: usize = 3;
+/// Minimum amount of laps needed before recommending a change
+const MIN_BRAKE_LAPS: usize = 2;
+/// Change of the brake temperatures per click of brake duct
+const DEGREES_PER_CLICK: f32 = 25.0;
+/// Change of the brake temperatures per degree of ambient temperature
+const DEGREES_PER_AMBIENT: f32 = 2.0;
+/// Largest ambient temperature change the forecast is allowed to predict
+const MAX_AMBIENT_CHANGE: f32 = 5.0;
+/// Pad life used up per lap above which the pads are considered overheating
+const MAX_PAD_WEAR: f32 = 0.05;
+
+/// Window the brake temperatures should be in for a pad compound, in °C
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BrakeTarget {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl BrakeTarget {
+    /// Window for the pad compound as indexed in the setup, pad 1 being 0
+    pub fn for_pad(pad: i32) -> Self {
+        match pad {
+            0 => BrakeTarget {
+                min: 325.0,
+                max: 650.0,
+            },
+            2 => BrakeTarget {
+                min: 275.0,
+                max: 550.0,
+            },
+            3 => BrakeTarget {
+                min: 250.0,
+                max: 500.0,
+            },
+            _ => BrakeTarget {
+                min: 300.0,
+                max: 600.0,
+            },
+        }
+    }
+
+    pub fn ideal(&self) -> f32 {
+        (self.min + self.max) / 2.0
+    }
+}
+
+/// Brake duct change for the race setups computed from the measured brake temperatures and pad wear
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BrakeDuctRecommendation {
+    /// Brake pad compounds the laps were driven on in front, rear order
+    pub pads: [i32; 2],
+    /// Amount of laps the measurement is based on
+    pub laps: usize,
+    /// Average brake temperatures per axle
+    pub temperature: [f32; 2],
+    /// Peak brake temperatures per axle
+    pub peak: [f32; 2],
+    /// Pad life used up per lap and axle
+    pub pad_wear: [f32; 2],
+    /// Ambient temperature change expected over the race
+    pub ambient_change: f32,
+    /// Clicks to add to the front and rear brake ducts, opening them for positive values
+    pub clicks: [i32; 2],
+}
+
+impl BrakeDuctRecommendation {
+    /// Compute the recommendation from the most recent racing laps on the same pads as the last lap,
+    /// with the ambient temperature trend of all laps projected over `race_laps`
+    pub fn from_laps(laps: &[LapWheels], race_laps: f32) -> Option<Self> {
+        let last = laps.last()?;
+        let measured: Vec<&LapWheels> = laps
+            .iter()
+            .rev()
+            .filter(|lap| lap.brake_pads == last.brake_pads)
+            // out and in laps have the brakes cooling down in the pit lane
+            .filter(|lap| lap.stint_lap > 1)
+            .filter(|lap| axles(&lap.brake_temperature.avg).iter().all(|t| *t > 0.0))
+            .take(BRAKE_LAPS)
+            .collect();
+
+        if measured.len() < MIN_BRAKE_LAPS {
+            return None;
+        }
+
+        let count = measured.len() as f32;
+        let average = |f: &dyn Fn(&LapWheels) -> [f32; 2]| -> [f32; 2] {
+            let sum = measured
+                .iter()
+                .map(|lap| f(lap))
+                .fold([0.0; 2], |sum, axle| [sum[0] + axle[0], sum[1] + axle[1]]);
+            sum.map(|s| s / count)
+        };
+        let temperature = average(&|lap| axles(&lap.brake_temperature.avg));
+        let peak = average(&|lap| axles(&lap.brake_temperature.max));
+        let pad_wear = average(&|lap| axles(&lap.pad_wear));
+
+        let ambient_change = ambient_change(laps, race_laps);
+        let shift = ambient_change * DEGREES_PER_AMBIENT;
+
+        let clicks = std::array::from_fn(|axle| {
+            let target = BrakeTarget::for_pad(last.brake_pads[axle]);
+            clicks(
+                &target,
+                temperature[axle] + shift,
+                peak[axle] + shift,
+                pad_wear[axle],
+            )
+        });
+
+        Some(BrakeDuctRecommendation {
+            pads: last.brake_pads,
+            laps: measured.len(),
+            temperature,
+            peak,
+            pad_wear,
+            ambient_change,
+            clicks,
+        })
+    }
+
+    /// Recommendation does not change any brake duct
+    pub fn is_empty(&self) -> bool {
+        self.clicks.iter().all(|c| *c == 0)
+    }
+}
+
+/// Clicks of brake duct for one axle, opening the ducts when the peaks go over the window
+/// or the pads wear quickly, closing them when the brakes run below it
+fn clicks(target: &BrakeTarget, temperature: f32, peak: f32, pad_wear: f32) -> i32 {
+    if peak > target.max {
+        ((peak - target.max) / DEGREES_PER_CLICK).ceil() as i32
+    } else if temperature < target.min {
+        -((target.min - temperature) / DEGREES_PER_CLICK).ceil() as i32
+    } else if pad_wear > MAX_PAD_WEAR && temperature > target.ideal() {
+        1
+    } else {
+        0
+    }
+}
+
+/// Ambient temperature change expected on average over the rest of the race,
+/// extrapolated from the temperature trend of the laps driven so far
+fn ambient_change(laps: &[LapWheels], race_laps: f32) -> f32 {
+    let Some(last) = laps.last() else {
+        return 0.0;
+    };
+
+    let mut fit = LinearFit::default();
+    laps.iter()
+        .filter(|lap| lap.ambient_temperature > 0.0)
+        .for_each(|lap| fit.add(lap.number as f32, lap.ambient_temperature));
+
+    let now = last.number as f32;
+    match (fit.predict(now), fit.predict(now + race_laps / 2.0)) {
+        (Some(now), Some(race)) => (race - now).clamp(-MAX_AMBIENT_CHANGE, MAX_AMBIENT_CHANGE),
+        _ => 0.0,
+    }
+}
+
+fn axles(wheels: &Wheels<f32>) -> [f32; 2] {
+    [
+        (wheels.front_left + wheels.front_right) / 2.0,
+        (wheels.rear_left + wheels.rear_right) / 2.0,
+    ]
+}
+
+#[cfg(test)]
+fn lap(number: i32, temperature: (f32, f32), peak: (f32, f32), ambient_temperature: f32) -> LapWheels {
+    let mut lap = LapWheels {
+        number,
+        stint_lap: number,
+        ambient_temperature,
+        brake_pads: [0, 0],
+        ..Default::default()
+    };
+    lap.brake_temperature.avg = (temperature.0, temperature.0, temperature.1, temperature.1).into();
+    lap.brake_temperature.max = (peak.0, peak.0, peak.1, peak.1).into();
+    lap
+}
+
+#[test]
+fn test_brake_duct_recommendation() {
+    let laps = [lap(1, (200.0, 200.0), (300.0, 300.0), 20.0)];
+    assert_eq!(None, BrakeDuctRecommendation::from_laps(&laps, 30.0));
+
+    let laps = [
+        lap(1, (200.0, 200.0), (300.0, 300.0), 20.0),
+        lap(2, (450.0, 250.0), (700.0, 450.0), 20.0),
+        lap(3, (450.0, 250.0), (700.0, 450.0), 20.0),
+    ];
+    let recommendation = BrakeDuctRecommendation::from_laps(&laps, 30.0).unwrap();
+
+    assert_eq!(recommendation.laps, 2);
+    assert_eq!(recommendation.ambient_change, 0.0);
+    assert_eq!(recommendation.clicks, [2, -3]);
+    assert!(!recommendation.is_empty());
+}
+
+#[test]
+fn test_brake_duct_forecast() {
+    let laps = [
+        lap(2, (450.0, 450.0), (645.0, 500.0), 20.0),
+        lap(3, (450.0, 450.0), (645.0, 500.0), 21.0),
+        lap(4, (450.0, 450.0), (645.0, 500.0), 22.0),
+    ];
+    let recommendation = BrakeDuctRecommendation::from_laps(&laps, 30.0).unwrap();
+
+    assert_eq!(recommendation.ambient_change, MAX_AMBIENT_CHANGE);
+    assert_eq!(recommendation.clicks, [1, 0]);
+}
@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::telemetry::LapWheels;
+
+use super::{Parameter, SetupType, Tyres};
+
+/// Laps at the start of a stint the build up is modelled for
+const BUILDUP_LAPS: usize = 4;
+/// Laps after the build up averaged to get the stable pressure of a stint
+const STABLE_LAPS: usize = 2;
+/// Lap of the stint qualifying setups should hit the target pressure on
+const QUALI_LAP: usize = 2;
+/// Laps of a race stint the pressure should be on target on average
+const RACE_LAPS: usize = 20;
+
+/// Variance of the track temperatures needed before fitting a slope
+const MIN_VARIANCE: f64 = 0.25;
+
+/// Running least squares fit of a value against the track temperature
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LinearFit {
+    n: f64,
+    sum_x: f64,
+    sum_y: f64,
+    sum_xy: f64,
+    sum_xx: f64,
+}
+
+impl LinearFit {
+    pub fn add(&mut self, x: f32, y: f32) {
+        let (x, y) = (x as f64, y as f64);
+        self.n += 1.0;
+        self.sum_x += x;
+        self.sum_y += y;
+        self.sum_xy += x * y;
+        self.sum_xx += x * x;
+    }
+
+    /// Predicted value at `x`, falls back to the mean while the samples share about the same temperature
+    pub fn predict(&self, x: f32) -> Option<f32> {
+        if self.n == 0.0 {
+            return None;
+        }
+
+        let variance = (self.n * self.sum_xx - self.sum_x * self.sum_x) / (self.n * self.n);
+        let slope = if variance > MIN_VARIANCE {
+            (self.n * self.sum_xy - self.sum_x * self.sum_y) / (variance * self.n * self.n)
+        } else {
+            0.0
+        };
+        let intercept = (self.sum_y - slope * self.sum_x) / self.n;
+
+        Some((intercept + slope * x as f64) as f32)
+    }
+}
+
+/// Model of how far the hot pressures are below their stable value over the first laps of a stint.
+///
+/// Each stint lap gets a fit per corner against the track temperature, as colder tracks take
+/// longer to bring the tyres up to pressure. The ambient temperature is left to the weather adjustment.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PressureBuildup {
+    /// Fits per tyre compound, stint lap and corner
+    compounds: BTreeMap<i32, Vec<[LinearFit; 4]>>,
+}
+
+impl PressureBuildup {
+    /// Amount of laps a stint needs before it can be added to the model
+    pub const STINT_LAPS: usize = BUILDUP_LAPS + STABLE_LAPS;
+
+    /// Add the first laps of a stint, ordered by their lap in the stint
+    pub fn add_stint(&mut self, stint: &[LapWheels]) {
+        if stint.len() < Self::STINT_LAPS {
+            return;
+        }
+
+        let stable = &stint[BUILDUP_LAPS..Self::STINT_LAPS];
+        let stable: [f32; 4] = std::array::from_fn(|corner| {
+            stable.iter().map(|lap| pressures(lap)[corner]).sum::<f32>() / STABLE_LAPS as f32
+        });
+
+        let compound = if stint[0].rain_tyres {
+            Tyres::WET_COMPOUND
+        } else {
+            Tyres::DRY_COMPOUND
+        };
+        let fits = self.compounds.entry(compound).or_default();
+        fits.resize(BUILDUP_LAPS, Default::default());
+
+        stint[..BUILDUP_LAPS]
+            .iter()
+            .zip(fits.iter_mut())
+            .for_each(|(lap, fits)| {
+                let pressures = pressures(lap);
+                fits.iter_mut().enumerate().for_each(|(corner, fit)| {
+                    fit.add(lap.track_temperature, pressures[corner] - stable[corner])
+                });
+            });
+    }
+
+    /// Predicted offset to the stable hot pressure on a lap of the stint, starting at 1
+    pub fn offset(&self, compound: i32, lap: usize, track_temperature: f32) -> [f32; 4] {
+        self.compounds
+            .get(&compound)
+            .and_then(|fits| fits.get(lap.checked_sub(1)?))
+            .map(|fits| std::array::from_fn(|corner| fits[corner].predict(track_temperature).unwrap_or(0.0)))
+            .unwrap_or_default()
+    }
+
+    /// Cold pressure clicks to add so the hot pressure is on target when it matters for the setup type,
+    /// on the push lap in qualifying and on average over a stint otherwise
+    pub fn clicks(
+        &self,
+        compound: i32,
+        setup_type: SetupType,
+        track_temperature: f32,
+        pressure: &Parameter,
+    ) -> [i32; 4] {
+        let offset = match setup_type {
+            SetupType::Qualifying => self.offset(compound, QUALI_LAP, track_temperature),
+            SetupType::Base | SetupType::Race => {
+                let sum = (1..=RACE_LAPS)
+                    .map(|lap| self.offset(compound, lap, track_temperature))
+                    .fold([0.0; 4], |mut sum, offset| {
+                        sum.iter_mut().zip(offset).for_each(|(s, o)| *s += o);
+                        sum
+                    });
+                sum.map(|s| s / RACE_LAPS as f32)
+            }
+        };
+
+        offset.map(|o| (-o / pressure.step).round() as i32)
+    }
+
+    /// Tyre compounds the model has data for
+    pub fn compounds(&self) -> impl Iterator<Item = i32> + '_ {
+        self.compounds.keys().copied()
+    }
+}
+
+fn pressures(lap: &LapWheels) -> [f32; 4] {
+    let p = &lap.tyre_pressure.avg;
+    [p.front_left, p.front_right, p.rear_left, p.rear_right]
+}
+
+#[cfg(test)]
+fn lap(stint_lap: i32, pressure: f32, track_temperature: f32) -> LapWheels {
+    let mut lap = LapWheels {
+        stint_lap,
+        track_temperature,
+        ..Default::default()
+    };
+    lap.tyre_pressure.avg = (pressure, pressure, pressure, pressure).into();
+    lap
+}
+
+#[test]
+fn test_linear_fit() {
+    let mut fit = LinearFit::default();
+    assert_eq!(fit.predict(20.0), None);
+
+    fit.add(20.0, -1.0);
+    assert_eq!(fit.predict(30.0), Some(-1.0));
+
+    fit.add(30.0, -0.5);
+    assert_eq!(fit.predict(25.0), Some(-0.75));
+}
+
+#[test]
+fn test_pressure_buildup() {
+    let mut buildup = PressureBuildup::default();
+    buildup.add_stint(&[lap(1, 25.0, 30.0), lap(2, 26.0, 30.0)]);
+    assert_eq!(buildup, PressureBuildup::default());
+
+    let pressures = [25.0, 26.0, 26.4, 26.6, 26.6, 26.6];
+    let stint: Vec<LapWheels> = pressures
+        .iter()
+        .enumerate()
+        .map(|(i, p)| lap(i as i32 + 1, *p, 30.0))
+        .collect();
+    buildup.add_stint(&stint);
+
+    let dry = Tyres::DRY_COMPOUND;
+    let pressure = super::CarParameters::default().tyre_pressure;
+    assert!((buildup.offset(dry, 1, 30.0)[0] + 1.6).abs() < 0.001);
+    assert_eq!(buildup.offset(Tyres::WET_COMPOUND, 1, 30.0), [0.0; 4]);
+    assert_eq!(
+        buildup.clicks(dry, SetupType::Qualifying, 30.0, &pressure),
+        [6, 6, 6, 6]
+    );
+    assert_eq!(
+        buildup.clicks(dry, SetupType::Race, 30.0, &pressure),
+        [1, 1, 1, 1]
+    );
+}
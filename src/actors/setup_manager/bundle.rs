@@ -0,0 +1,418 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::{diff, FieldChange, Setup, SetupError, SetupFile, HISTORY_FOLDER};
+
+/// Version of the bundle format written by this version of the app
+const BUNDLE_FORMAT: u32 = 1;
+/// File extension of setup bundles
+pub const BUNDLE_EXTENSION: &str = "vapor";
+
+/// Setup folders of a car and track
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct CarTrack {
+    pub car: String,
+    pub track: String,
+}
+
+/// Contents of a bundle, readable without going through the files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format: u32,
+    pub created: DateTime<Local>,
+    pub author: String,
+    pub tracks: Vec<BundleTrack>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleTrack {
+    #[serde(flatten)]
+    pub car_track: CarTrack,
+    /// Names of the files of the track in the bundle
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleFile {
+    #[serde(flatten)]
+    pub car_track: CarTrack,
+    pub name: String,
+    pub contents: String,
+}
+
+/// Templates, meta files and notes of a selection of cars and tracks in a single file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetupBundle {
+    pub manifest: BundleManifest,
+    pub files: Vec<BundleFile>,
+}
+
+/// File of a bundle that would replace a different file in the template folder
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleConflict {
+    pub car_track: CarTrack,
+    pub name: String,
+    /// Changes to the existing setup, empty for files that are not setups
+    pub changes: Vec<FieldChange>,
+}
+
+/// Tracks and templates in the template folder and bundles that can be imported
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SetupLibrary {
+    pub tracks: Vec<CarTrack>,
+    /// Names of the templates of every track
+    pub templates: BTreeMap<CarTrack, Vec<String>>,
+    /// Folder bundles are exported to and imported from
+    pub folder: PathBuf,
+    pub bundles: Vec<PathBuf>,
+}
+
+impl SetupLibrary {
+    pub fn read(template_folder: &Path, bundle_folder: &Path) -> Self {
+        let tracks = library(template_folder);
+        let templates = tracks
+            .iter()
+            .map(|car_track| (car_track.clone(), template_names(template_folder, car_track)))
+            .collect();
+
+        SetupLibrary {
+            tracks,
+            templates,
+            folder: bundle_folder.to_owned(),
+            bundles: bundles(bundle_folder),
+        }
+    }
+}
+
+/// Bundle read from disk with the conflicts importing it would cause
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundlePreview {
+    pub path: PathBuf,
+    pub manifest: BundleManifest,
+    pub conflicts: Vec<BundleConflict>,
+}
+
+impl SetupBundle {
+    /// Collect every file of the selected tracks, leaving out the version history
+    pub fn export(template_folder: &Path, selection: &[CarTrack], author: &str) -> Result<Self, SetupError> {
+        let mut tracks = Vec::new();
+        let mut files = Vec::new();
+
+        for car_track in selection {
+            let folder = template_folder.join(&car_track.car).join(&car_track.track);
+            let mut names = Vec::new();
+
+            for entry in std::fs::read_dir(&folder)?.filter_map(|f| f.ok()) {
+                let path = entry.path();
+                let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                    continue;
+                };
+                if path.is_dir() || name == HISTORY_FOLDER {
+                    continue;
+                }
+
+                files.push(BundleFile {
+                    car_track: car_track.clone(),
+                    name: name.to_owned(),
+                    contents: std::fs::read_to_string(&path)?,
+                });
+                names.push(name.to_owned());
+            }
+
+            names.sort();
+            tracks.push(BundleTrack {
+                car_track: car_track.clone(),
+                files: names,
+            });
+        }
+
+        Ok(SetupBundle {
+            manifest: BundleManifest {
+                format: BUNDLE_FORMAT,
+                created: Local::now(),
+                author: author.to_owned(),
+                tracks,
+            },
+            files,
+        })
+    }
+
+    /// File name of the bundle, named after its track if it only has one
+    pub fn file_name(&self) -> String {
+        let name = match self.manifest.tracks.as_slice() {
+            [track] => format!("{} {}", track.car_track.car, track.car_track.track),
+            _ => "Setups".to_owned(),
+        };
+
+        format!(
+            "{name} {}.{BUNDLE_EXTENSION}",
+            self.manifest.created.format("%Y-%m-%d %H-%M-%S")
+        )
+    }
+
+    pub fn read(path: &Path) -> Result<Self, SetupError> {
+        let data = std::fs::read_to_string(path)?;
+        let bundle: SetupBundle = serde_json::from_str(&data)?;
+
+        if bundle.manifest.format > BUNDLE_FORMAT {
+            return Err(SetupError::InvalidBundle(format!(
+                "bundle format {} is newer than supported",
+                bundle.manifest.format
+            )));
+        }
+        // Names end up in paths, so they must not be able to leave the template folder
+        if let Some(file) = bundle.files.iter().find(|f| {
+            !is_plain_name(&f.car_track.car) || !is_plain_name(&f.car_track.track) || !is_plain_name(&f.name)
+        }) {
+            return Err(SetupError::InvalidBundle(format!(
+                "invalid file name {}",
+                file.name
+            )));
+        }
+
+        Ok(bundle)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), SetupError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        debug!("saving setup bundle to {:?}", path);
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+
+        Ok(())
+    }
+
+    /// Files that already exist in the template folder with different contents
+    pub fn conflicts(&self, template_folder: &Path) -> Vec<BundleConflict> {
+        self.files
+            .iter()
+            .filter_map(|file| {
+                let path = file.path(template_folder);
+                let existing = std::fs::read_to_string(path).ok()?;
+                if existing == file.contents {
+                    return None;
+                }
+
+                let changes = match (
+                    serde_json::from_str::<Setup>(&existing),
+                    serde_json::from_str::<Setup>(&file.contents),
+                ) {
+                    (Ok(existing), Ok(imported)) => diff(&existing, &imported),
+                    _ => Vec::new(),
+                };
+
+                Some(BundleConflict {
+                    car_track: file.car_track.clone(),
+                    name: file.name.clone(),
+                    changes,
+                })
+            })
+            .collect()
+    }
+
+    /// Write the files into the template folder, files with conflicts are only replaced
+    /// when overwriting. Returns the tracks that had files written.
+    pub fn import(&self, template_folder: &Path, overwrite: bool) -> Result<Vec<CarTrack>, SetupError> {
+        let conflicts = self.conflicts(template_folder);
+        let mut imported: Vec<CarTrack> = Vec::new();
+
+        for file in &self.files {
+            let path = file.path(template_folder);
+            let conflict = conflicts
+                .iter()
+                .any(|c| c.car_track == file.car_track && c.name == file.name);
+            if conflict && !overwrite {
+                debug!("skipping conflicting file {:?}", path);
+                continue;
+            }
+
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, &file.contents)?;
+
+            if !imported.contains(&file.car_track) {
+                imported.push(file.car_track.clone());
+            }
+        }
+
+        Ok(imported)
+    }
+}
+
+impl BundleFile {
+    fn path(&self, template_folder: &Path) -> PathBuf {
+        template_folder
+            .join(&self.car_track.car)
+            .join(&self.car_track.track)
+            .join(&self.name)
+    }
+}
+
+/// Car and track folders in the template folder
+pub fn library(template_folder: &Path) -> Vec<CarTrack> {
+    let folders = |path: &Path| -> Vec<String> {
+        std::fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .filter_map(|f| f.ok())
+                    .filter(|f| f.path().is_dir())
+                    .filter_map(|f| f.file_name().to_str().map(str::to_owned))
+                    .filter(|name| name != HISTORY_FOLDER)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let mut library: Vec<CarTrack> = folders(template_folder)
+        .into_iter()
+        .flat_map(|car| {
+            folders(&template_folder.join(&car))
+                .into_iter()
+                .map(move |track| CarTrack {
+                    car: car.clone(),
+                    track,
+                })
+        })
+        .collect();
+    library.sort();
+
+    library
+}
+
+/// Names of the templates of a track, sorted
+pub fn template_names(template_folder: &Path, car_track: &CarTrack) -> Vec<String> {
+    let folder = template_folder.join(&car_track.car).join(&car_track.track);
+    let Ok(entries) = std::fs::read_dir(folder) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|f| f.ok())
+        .map(|f| f.path())
+        .filter(|path| !path.is_dir() && path.extension().is_some_and(|x| x == "json"))
+        .filter_map(|path| SetupFile::parse_name(&path).ok())
+        .map(|(name, _, _)| name)
+        .collect();
+    names.sort();
+
+    names
+}
+
+/// Bundles in the bundle folder, newest first
+pub fn bundles(bundle_folder: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(bundle_folder) else {
+        return Vec::new();
+    };
+
+    let mut bundles: Vec<PathBuf> = entries
+        .filter_map(|f| f.ok())
+        .map(|f| f.path())
+        .filter(|path| path.extension().is_some_and(|x| x == BUNDLE_EXTENSION))
+        .collect();
+    bundles.sort();
+    bundles.reverse();
+
+    bundles
+}
+
+/// Name of a single file or folder that can't reach outside of the folder it is joined to
+pub(crate) fn is_plain_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\', ':'])
+}
+
+#[test]
+fn test_bundle() -> Result<(), SetupError> {
+    let folder = crate::TestFolder::new("bundle");
+
+    let source = folder.join("source");
+    let target = folder.join("target");
+    let track = source.join("car").join("track");
+    std::fs::create_dir_all(track.join(HISTORY_FOLDER))?;
+
+    let mut setup = super::setup::setup();
+    std::fs::write(track.join("21c 28c Race.json"), serde_json::to_string(&setup)?)?;
+    std::fs::write(track.join("notes.md"), "notes")?;
+
+    let selection = library(&source);
+    assert_eq!(
+        selection,
+        [CarTrack {
+            car: "car".to_owned(),
+            track: "track".to_owned(),
+        }]
+    );
+
+    assert_eq!(
+        SetupLibrary::read(&source, &folder).templates[&selection[0]],
+        ["Race"]
+    );
+
+    let bundle = SetupBundle::export(&source, &selection, "Author")?;
+    assert_eq!(bundle.manifest.tracks[0].files, ["21c 28c Race.json", "notes.md"]);
+
+    let path = folder.join(format!("bundle.{BUNDLE_EXTENSION}"));
+    bundle.save(&path)?;
+    let bundle = SetupBundle::read(&path)?;
+    assert_eq!(bundles(&folder), [path]);
+
+    assert!(bundle.conflicts(&target).is_empty());
+    assert_eq!(bundle.import(&target, false)?, selection);
+
+    // Change the imported setup to conflict with the bundle
+    setup.advanced_setup.aero_balance.rear_wing += 2;
+    let imported = target.join("car").join("track").join("21c 28c Race.json");
+    std::fs::write(&imported, serde_json::to_string(&setup)?)?;
+
+    let conflicts = bundle.conflicts(&target);
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].changes[0].clicks(), Some(-2));
+
+    bundle.import(&target, false)?;
+    assert_eq!(
+        serde_json::from_str::<Setup>(&std::fs::read_to_string(&imported)?)?,
+        setup
+    );
+    bundle.import(&target, true)?;
+    assert!(bundle.conflicts(&target).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_bundle_invalid_name() {
+    let folder = crate::TestFolder::new("bundle-invalid");
+
+    let bundle = SetupBundle {
+        manifest: BundleManifest {
+            format: BUNDLE_FORMAT,
+            created: Local::now(),
+            author: String::new(),
+            tracks: Vec::new(),
+        },
+        files: vec![BundleFile {
+            car_track: CarTrack {
+                car: "..".to_owned(),
+                track: "track".to_owned(),
+            },
+            name: "setup.json".to_owned(),
+            contents: String::new(),
+        }],
+    };
+    let path = folder.join("invalid.vapor");
+    bundle.save(&path).unwrap();
+
+    assert!(matches!(
+        SetupBundle::read(&path),
+        Err(SetupError::InvalidBundle(_))
+    ));
+}
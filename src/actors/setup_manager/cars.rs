@@ -0,0 +1,251 @@
+use std::{collections::HashMap, fmt::Display, sync::LazyLock};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error};
+
+/// Car parameters shipped with the program, only listing the values that differ from a generic GT3.
+/// So far only the brake bias is known per car, the generic ranges are only used for warnings.
+static BUILTIN_CARS: &str = include_str!("cars.json");
+
+static CARS: LazyLock<HashMap<String, CarParameters>> = LazyLock::new(CarParameters::load_database);
+static GENERIC: LazyLock<CarParameters> = LazyLock::new(CarParameters::default);
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Unit {
+    #[default]
+    Click,
+    Psi,
+    Degree,
+    Millimeter,
+    NewtonMeter,
+    Percent,
+    Liter,
+}
+
+impl Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Unit::Click => write!(f, ""),
+            Unit::Psi => write!(f, " psi"),
+            Unit::Degree => write!(f, "°"),
+            Unit::Millimeter => write!(f, " mm"),
+            Unit::NewtonMeter => write!(f, " Nm"),
+            Unit::Percent => write!(f, "%"),
+            Unit::Liter => write!(f, " l"),
+        }
+    }
+}
+
+/// Mapping of a setup value stored as click index to its physical value
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Parameter {
+    /// Physical value at click 0
+    pub base: f32,
+    /// Change of the physical value per click
+    pub step: f32,
+    /// Lowest legal click
+    pub min: i32,
+    /// Highest legal click
+    pub max: i32,
+    #[serde(default)]
+    pub unit: Unit,
+    /// Range is given for the car, adjustments are only kept within known ranges
+    #[serde(skip_serializing, default = "Parameter::known")]
+    pub known: bool,
+}
+
+impl Parameter {
+    const fn new(base: f32, step: f32, min: i32, max: i32, unit: Unit) -> Self {
+        Parameter {
+            base,
+            step,
+            min,
+            max,
+            unit,
+            known: false,
+        }
+    }
+
+    /// Parameters read from a car database are known for the car
+    fn known() -> bool {
+        true
+    }
+
+    const fn clicks(max: i32) -> Self {
+        Parameter::new(0.0, 1.0, 0, max, Unit::Click)
+    }
+
+    pub fn value(&self, clicks: i32) -> f32 {
+        self.base + clicks as f32 * self.step
+    }
+
+    /// Clicks within the range of the car, generic ranges only keep them from going negative
+    pub fn clamp(&self, clicks: i32) -> i32 {
+        if self.known {
+            clicks.clamp(self.min, self.max)
+        } else {
+            clicks.max(0)
+        }
+    }
+
+    pub fn contains(&self, clicks: i32) -> bool {
+        (self.min..=self.max).contains(&clicks)
+    }
+
+    /// Physical value with its unit, using as many decimals as the step needs
+    pub fn format(&self, clicks: i32) -> String {
+        let value = self.value(clicks);
+        if self.step >= 1.0 {
+            format!("{value:.0}{}", self.unit)
+        } else if self.step >= 0.1 {
+            format!("{value:.1}{}", self.unit)
+        } else {
+            format!("{value:.2}{}", self.unit)
+        }
+    }
+}
+
+/// Parameter that differs between the front and rear axle
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Axle {
+    pub front: Parameter,
+    pub rear: Parameter,
+}
+
+impl Axle {
+    const fn same(parameter: Parameter) -> Self {
+        Axle {
+            front: parameter,
+            rear: parameter,
+        }
+    }
+
+    /// Parameter for a corner in FL, FR, RL, RR order
+    pub fn corner(&self, index: usize) -> &Parameter {
+        if index < 2 {
+            &self.front
+        } else {
+            &self.rear
+        }
+    }
+}
+
+/// Physical units and legal ranges of the setup values of a car
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CarParameters {
+    pub tyre_pressure: Parameter,
+    pub camber: Axle,
+    pub toe: Axle,
+    pub ride_height: Axle,
+    pub anti_roll_bar: Axle,
+    pub brake_duct: Parameter,
+    pub splitter: Parameter,
+    pub rear_wing: Parameter,
+    pub brake_bias: Parameter,
+    pub brake_torque: Parameter,
+    pub preload: Parameter,
+    pub fuel: Parameter,
+}
+
+impl Default for CarParameters {
+    /// Values of a generic GT3 car, the pressure and fuel ranges are the widest of any car
+    fn default() -> Self {
+        CarParameters {
+            tyre_pressure: Parameter::new(20.3, 0.1, 0, 147, Unit::Psi),
+            camber: Axle {
+                front: Parameter::new(-4.0, 0.1, 0, 25, Unit::Degree),
+                rear: Parameter::new(-3.5, 0.1, 0, 25, Unit::Degree),
+            },
+            toe: Axle::same(Parameter::new(-0.4, 0.01, 0, 80, Unit::Degree)),
+            ride_height: Axle {
+                front: Parameter::new(54.0, 1.0, 0, 30, Unit::Millimeter),
+                rear: Parameter::new(55.0, 1.0, 0, 40, Unit::Millimeter),
+            },
+            anti_roll_bar: Axle::same(Parameter::clicks(20)),
+            brake_duct: Parameter::clicks(6),
+            splitter: Parameter::clicks(5),
+            rear_wing: Parameter::clicks(12),
+            brake_bias: Parameter::new(57.0, 0.2, 0, 40, Unit::Percent),
+            brake_torque: Parameter::new(80.0, 1.0, 0, 20, Unit::Percent),
+            preload: Parameter::new(20.0, 10.0, 0, 30, Unit::NewtonMeter),
+            fuel: Parameter::new(0.0, 1.0, 0, 132, Unit::Liter),
+        }
+    }
+}
+
+impl CarParameters {
+    /// Parameters of the car, falling back to a generic GT3 for unknown cars
+    pub fn get(car_name: &str) -> &'static CarParameters {
+        CARS.get(car_name).unwrap_or_else(|| {
+            debug!("no parameters for car {car_name}, using generic values");
+            &GENERIC
+        })
+    }
+
+    /// Parameter of a setup field by its key in the setup json and its index in the field
+    /// if it is an array, None for fields without a physical unit
+    pub fn field(&self, key: &str, index: Option<usize>) -> Option<&Parameter> {
+        let index = index.unwrap_or_default();
+        match key {
+            "tyrePressure" => Some(&self.tyre_pressure),
+            "camber" => Some(self.camber.corner(index)),
+            "toe" => Some(self.toe.corner(index)),
+            "rideHeight" => Some(self.ride_height.corner(index)),
+            "aRBFront" => Some(&self.anti_roll_bar.front),
+            "aRBRear" => Some(&self.anti_roll_bar.rear),
+            "brakeDuct" => Some(&self.brake_duct),
+            "splitter" => Some(&self.splitter),
+            "rearWing" => Some(&self.rear_wing),
+            "brakeBias" => Some(&self.brake_bias),
+            "brakeTorque" => Some(&self.brake_torque),
+            "preload" => Some(&self.preload),
+            "fuel" | "fuelToAdd" => Some(&self.fuel),
+            _ => None,
+        }
+    }
+
+    /// Read the builtin car database and extend it with the users `cars.json`
+    fn load_database() -> HashMap<String, CarParameters> {
+        let mut cars: HashMap<String, CarParameters> =
+            serde_json::from_str(BUILTIN_CARS).expect("builtin car database is invalid");
+
+        let path = crate::data_folder().join("cars.json");
+        if let Ok(data) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<HashMap<String, CarParameters>>(&data) {
+                Ok(user) => cars.extend(user),
+                Err(e) => error!("failed to parse car database {path:?}: {e}"),
+            }
+        }
+
+        cars
+    }
+}
+
+#[test]
+fn test_builtin_cars() {
+    let cars: HashMap<String, CarParameters> = serde_json::from_str(BUILTIN_CARS).unwrap();
+    let porsche = &cars["porsche_991ii_gt3_r"];
+
+    assert_eq!(porsche.brake_bias.base, 43.0);
+    assert_eq!(porsche.brake_bias.clamp(45), 40);
+    assert_eq!(porsche.tyre_pressure, CarParameters::default().tyre_pressure);
+}
+
+#[test]
+fn test_parameter() {
+    let parameters = CarParameters::default();
+
+    assert_eq!(parameters.tyre_pressure.format(62), "26.5 psi");
+    assert_eq!(parameters.tyre_pressure.format(147), "35.0 psi");
+    assert_eq!(parameters.tyre_pressure.clamp(150), 150);
+    assert_eq!(parameters.tyre_pressure.clamp(-2), 0);
+    assert_eq!(parameters.camber.corner(3).format(10), "-2.5°");
+    assert_eq!(parameters.preload.format(3), "50 Nm");
+    assert!(!parameters.brake_duct.contains(7));
+    assert_eq!(
+        parameters.field("rideHeight", Some(2)),
+        Some(&parameters.ride_height.rear)
+    );
+    assert_eq!(parameters.field("bumpSlow", Some(0)), None);
+}
@@ -0,0 +1,180 @@
+use serde_json::Value;
+
+use super::{
+    fields::{Description, Segment},
+    CarParameters, Parameter, Setup,
+};
+
+/// A value that differs between two setups
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    /// Path of the field in the setup json, e.g. `basicSetup.tyres.tyrePressure[0]`
+    pub path: String,
+    /// Section of the setup the field is in, e.g. `Tyres`
+    pub section: String,
+    /// Readable name of the field including its corner, axle or pit stop
+    pub label: String,
+    /// Value in the first setup, None when the field only exists in the second one
+    pub from: Option<Value>,
+    /// Value in the second setup, None when the field only exists in the first one
+    pub to: Option<Value>,
+    /// Physical unit mapping of the field if it has one
+    pub parameter: Option<Parameter>,
+}
+
+impl FieldChange {
+    /// Change in clicks for whole number fields present in both setups
+    pub fn clicks(&self) -> Option<i64> {
+        Some(self.to.as_ref()?.as_i64()? - self.from.as_ref()?.as_i64()?)
+    }
+
+    /// Value in clicks and in physical units if known
+    pub fn format(&self, value: &Option<Value>) -> String {
+        match value {
+            None => "-".to_owned(),
+            Some(Value::Number(n)) => match (n.as_i64(), &self.parameter) {
+                (Some(clicks), Some(parameter)) if parameter.unit != Default::default() => {
+                    format!("{clicks} ({})", parameter.format(clicks as i32))
+                }
+                (Some(clicks), _) => clicks.to_string(),
+                (None, _) => format!("{:.3}", n.as_f64().unwrap_or_default()),
+            },
+            Some(Value::String(s)) => s.clone(),
+            Some(value) => value.to_string(),
+        }
+    }
+}
+
+/// Every field that differs between the two setups, sorted by their path in the setup json.
+///
+/// Physical units are taken from the car of the second setup.
+pub fn diff(from: &Setup, to: &Setup) -> Vec<FieldChange> {
+    let parameters = CarParameters::get(&to.car_name);
+    let (Ok(from), Ok(to)) = (serde_json::to_value(from), serde_json::to_value(to)) else {
+        return Vec::new();
+    };
+
+    let mut changes = Vec::new();
+    walk(parameters, &mut Vec::new(), Some(&from), Some(&to), &mut changes);
+    changes
+}
+
+fn walk<'a>(
+    parameters: &CarParameters,
+    path: &mut Vec<Segment<'a>>,
+    from: Option<&'a Value>,
+    to: Option<&'a Value>,
+    changes: &mut Vec<FieldChange>,
+) {
+    if from == to {
+        return;
+    }
+
+    // Fields only present on one side are walked as well to report every value of them
+    match (from, to) {
+        (Some(Value::Object(_)) | None, Some(Value::Object(_)) | None) => {
+            let from = from.and_then(Value::as_object);
+            let to = to.and_then(Value::as_object);
+            let mut keys: Vec<&String> = from.iter().chain(to.iter()).flat_map(|o| o.keys()).collect();
+            keys.sort();
+            keys.dedup();
+
+            for key in keys {
+                path.push(Segment::Key(key));
+                walk(
+                    parameters,
+                    path,
+                    from.and_then(|o| o.get(key)),
+                    to.and_then(|o| o.get(key)),
+                    changes,
+                );
+                path.pop();
+            }
+        }
+        (Some(Value::Array(_)) | None, Some(Value::Array(_)) | None) => {
+            let from = from.and_then(Value::as_array);
+            let to = to.and_then(Value::as_array);
+            let len = from
+                .iter()
+                .chain(to.iter())
+                .map(|a| a.len())
+                .max()
+                .unwrap_or_default();
+
+            for index in 0..len {
+                path.push(Segment::Index(index));
+                walk(
+                    parameters,
+                    path,
+                    from.and_then(|a| a.get(index)),
+                    to.and_then(|a| a.get(index)),
+                    changes,
+                );
+                path.pop();
+            }
+        }
+        (from, to) => changes.push(change(parameters, path, from, to)),
+    }
+}
+
+fn change(
+    parameters: &CarParameters,
+    path: &[Segment<'_>],
+    from: Option<&Value>,
+    to: Option<&Value>,
+) -> FieldChange {
+    let description = Description::new(parameters, path);
+
+    FieldChange {
+        path: description.path,
+        section: description.section,
+        label: description.label,
+        from: from.cloned(),
+        to: to.cloned(),
+        parameter: description.parameter,
+    }
+}
+
+#[test]
+fn test_diff() {
+    let from = super::setup::setup();
+    assert_eq!(diff(&from, &from), Vec::new());
+
+    let mut to = from.clone();
+    to.basic_setup.tyres.tyre_pressure[2] += 3;
+    to.basic_setup.strategy.pit_strategy[0].tyre_set = 3;
+    to.advanced_setup.aero_balance.brake_duct[1] = 5;
+    to.advanced_setup.mechanical_balance.arb_front = 12;
+
+    let changes = diff(&from, &to);
+    let labels: Vec<&str> = changes.iter().map(|c| c.label.as_str()).collect();
+    assert_eq!(
+        labels,
+        [
+            "Brake Duct Rear",
+            "ARB Front",
+            "Pit Stop 1 Tyre Set",
+            "Tyre Pressure RL"
+        ]
+    );
+
+    let pressure = &changes[3];
+    assert_eq!(pressure.path, "basicSetup.tyres.tyrePressure[2]");
+    assert_eq!(pressure.section, "Tyres");
+    assert_eq!(pressure.clicks(), Some(3));
+    assert_eq!(pressure.format(&pressure.to), "59 (26.2 psi)");
+    assert_eq!(changes[2].format(&changes[2].to), "3");
+}
+
+#[test]
+fn test_diff_pit_stops() {
+    let from = super::setup::setup();
+    let mut to = from.clone();
+    to.basic_setup.strategy.pit_strategy.clear();
+
+    let changes = diff(&from, &to);
+    assert!(changes
+        .iter()
+        .all(|c| c.to.is_none() && c.path.starts_with("basicSetup.strategy.pitStrategy[0]")));
+    assert_eq!(changes[0].format(&changes[0].to), "-");
+}
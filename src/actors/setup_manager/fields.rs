@@ -0,0 +1,257 @@
+use serde_json::Value;
+
+use super::{CarParameters, Parameter, Setup, SetupError};
+
+/// Corner names of the four value arrays in FL, FR, RL, RR order
+const CORNERS: [&str; 4] = ["FL", "FR", "RL", "RR"];
+/// Axle names of the two value arrays in front, rear order
+const AXLES: [&str; 2] = ["Front", "Rear"];
+
+/// Segment of the path to a value in the setup json
+#[derive(Debug, Clone, Copy)]
+pub(super) enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Description of a value in the setup json
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Description {
+    pub path: String,
+    pub section: String,
+    pub label: String,
+    pub parameter: Option<Parameter>,
+}
+
+impl Description {
+    pub fn new(parameters: &CarParameters, path: &[Segment<'_>]) -> Self {
+        let mut full_path = String::new();
+        for segment in path {
+            match segment {
+                Segment::Key(key) if full_path.is_empty() => full_path.push_str(key),
+                Segment::Key(key) => full_path.push_str(&format!(".{key}")),
+                Segment::Index(index) => full_path.push_str(&format!("[{index}]")),
+            }
+        }
+
+        // Last key of the path and the index into its array
+        let (key, index) = match path {
+            [.., Segment::Key(key), Segment::Index(index)] => (*key, Some(*index)),
+            [.., Segment::Key(key)] => (*key, None),
+            _ => ("", None),
+        };
+
+        let section = path
+            .iter()
+            .filter_map(|segment| match segment {
+                Segment::Key(key) => Some(*key),
+                Segment::Index(_) => None,
+            })
+            .nth(1)
+            .unwrap_or(key);
+
+        let mut label = String::new();
+        if let Some(stop) = path.iter().position(|s| matches!(s, Segment::Key("pitStrategy"))) {
+            if let Some(Segment::Index(stop)) = path.get(stop + 1) {
+                label.push_str(&format!("Pit Stop {} ", stop + 1));
+            }
+        }
+        label.push_str(&field_name(key));
+        if let Some(index) = index {
+            label.push(' ');
+            label.push_str(&index_name(key, index));
+        }
+
+        Description {
+            path: full_path,
+            section: field_name(section),
+            label,
+            parameter: parameters.field(key, index).copied(),
+        }
+    }
+}
+
+/// A single value of a setup
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetupField {
+    /// Path of the field in the setup json, e.g. `basicSetup.tyres.tyrePressure[0]`
+    pub path: String,
+    /// Section of the setup the field is in, e.g. `Tyres`
+    pub section: String,
+    /// Readable name of the field including its corner, axle or pit stop
+    pub label: String,
+    pub value: Value,
+    /// Physical unit mapping and range of the field if it has one
+    pub parameter: Option<Parameter>,
+}
+
+impl SetupField {
+    /// Value is stored in clicks, other numbers are computed by ACC or the fuel usage
+    pub fn is_clicks(&self) -> bool {
+        self.value.is_i64()
+    }
+
+    /// Why the value looks wrong for the car. It is still allowed, most cars only have the ranges
+    /// of a generic GT3 so far.
+    pub fn warning(&self) -> Option<String> {
+        let clicks = self.value.as_i64()?;
+        let parameter = self.parameter.as_ref()?;
+        (!parameter.contains(clicks as i32)).then(|| {
+            format!(
+                "{} is outside of {} to {}",
+                self.label, parameter.min, parameter.max
+            )
+        })
+    }
+}
+
+/// Every number of the setup with its description, sorted by their path in the setup json
+pub fn fields(setup: &Setup) -> Vec<SetupField> {
+    let parameters = CarParameters::get(&setup.car_name);
+    let Ok(value) = serde_json::to_value(setup) else {
+        return Vec::new();
+    };
+
+    let mut fields = Vec::new();
+    walk(parameters, &mut Vec::new(), &value, &mut fields);
+    fields
+}
+
+fn walk<'a>(
+    parameters: &CarParameters,
+    path: &mut Vec<Segment<'a>>,
+    value: &'a Value,
+    fields: &mut Vec<SetupField>,
+) {
+    match value {
+        Value::Object(object) => object.iter().for_each(|(key, value)| {
+            path.push(Segment::Key(key));
+            walk(parameters, path, value, fields);
+            path.pop();
+        }),
+        Value::Array(array) => array.iter().enumerate().for_each(|(index, value)| {
+            path.push(Segment::Index(index));
+            walk(parameters, path, value, fields);
+            path.pop();
+        }),
+        Value::Number(_) => {
+            let description = Description::new(parameters, path);
+            fields.push(SetupField {
+                path: description.path,
+                section: description.section,
+                label: description.label,
+                value: value.clone(),
+                parameter: description.parameter,
+            });
+        }
+        _ => {}
+    }
+}
+
+/// Set a field of the setup by its path in the setup json
+pub fn set_field(setup: &mut Setup, path: &str, value: Value) -> Result<(), SetupError> {
+    let pointer = format!("/{}", path.replace(['.', '['], "/").replace(']', ""));
+
+    let mut json = serde_json::to_value(&*setup)?;
+    let field = json.pointer_mut(&pointer).ok_or(SetupError::ParsePathError)?;
+    *field = value;
+    *setup = serde_json::from_value(json)?;
+
+    Ok(())
+}
+
+/// Fields of the setup that are out of the range of the car
+pub fn validate(setup: &Setup) -> Vec<SetupField> {
+    fields(setup)
+        .into_iter()
+        .filter(|field| field.warning().is_some())
+        .collect()
+}
+
+/// Name of an index into a setup array, the axle for brake ducts and the corner otherwise
+pub fn index_name(key: &str, index: usize) -> String {
+    let names: &[&str] = if key == "brakeDuct" { &AXLES } else { &CORNERS };
+    names
+        .get(index)
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| (index + 1).to_string())
+}
+
+/// Readable name of a setup json key, `bumpStopRateUp` becomes `Bump Stop Rate Up`
+pub fn field_name(key: &str) -> String {
+    match key {
+        "aRBFront" => return "ARB Front".to_owned(),
+        "aRBRear" => return "ARB Rear".to_owned(),
+        "tC1" => return "TC1".to_owned(),
+        "tC2" => return "TC2".to_owned(),
+        "abs" => return "ABS".to_owned(),
+        "eCUMap" => return "ECU Map".to_owned(),
+        "casterLF" => return "Caster FL".to_owned(),
+        "casterRF" => return "Caster FR".to_owned(),
+        "nPitStops" => return "Pit Stops".to_owned(),
+        "trackBopType" => return "Track BoP".to_owned(),
+        _ => {}
+    }
+
+    let mut name = String::new();
+    for (i, c) in key.chars().enumerate() {
+        if i == 0 {
+            name.extend(c.to_uppercase());
+        } else if c.is_uppercase() {
+            name.push(' ');
+            name.push(c);
+        } else {
+            name.push(c);
+        }
+    }
+    name
+}
+
+#[test]
+fn test_fields() {
+    let setup = super::setup::setup();
+    let fields = fields(&setup);
+
+    let pressure = fields
+        .iter()
+        .find(|field| field.path == "basicSetup.tyres.tyrePressure[1]")
+        .unwrap();
+    assert_eq!(pressure.section, "Tyres");
+    assert_eq!(pressure.label, "Tyre Pressure FR");
+    assert_eq!(pressure.value, 52);
+    assert!(pressure.is_clicks());
+
+    let stop = fields
+        .iter()
+        .find(|field| field.path == "basicSetup.strategy.pitStrategy[0].tyres.tyrePressure[3]")
+        .unwrap();
+    assert_eq!(stop.label, "Pit Stop 1 Tyre Pressure RR");
+
+    assert!(fields
+        .iter()
+        .any(|field| field.label == "Fuel Per Lap" && !field.is_clicks()));
+}
+
+#[test]
+fn test_set_field() -> Result<(), SetupError> {
+    let mut setup = super::setup::setup();
+
+    set_field(&mut setup, "advancedSetup.aeroBalance.brakeDuct[1]", 5.into())?;
+    assert_eq!(setup.advanced_setup.aero_balance.brake_duct, [3, 5]);
+    assert!(validate(&setup).is_empty());
+
+    // Fields without a range of the car are not checked
+    set_field(&mut setup, "basicSetup.electronics.tC1", (-1).into())?;
+    assert!(validate(&setup).is_empty());
+
+    set_field(&mut setup, "basicSetup.tyres.tyrePressure[0]", 150.into())?;
+    let warnings = validate(&setup);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].warning().unwrap(),
+        "Tyre Pressure FL is outside of 0 to 147"
+    );
+
+    assert!(set_field(&mut setup, "basicSetup.tyres.missing", 1.into()).is_err());
+    Ok(())
+}
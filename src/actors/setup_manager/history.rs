@@ -0,0 +1,151 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error};
+
+use super::{Setup, SetupError};
+
+/// Folder next to the templates of a track the versions are kept in
+pub const HISTORY_FOLDER: &str = ".history";
+
+/// Who saved a version of a template and why
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionInfo {
+    pub author: String,
+    pub note: String,
+}
+
+impl VersionInfo {
+    /// Info of versions snapshotted because the template changed outside of the app
+    pub fn external() -> Self {
+        VersionInfo {
+            author: "External".to_owned(),
+            note: "Changed outside of Vapor Manager".to_owned(),
+        }
+    }
+}
+
+/// Snapshot of a template at the time it was saved
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetupVersion {
+    pub timestamp: DateTime<Local>,
+    #[serde(flatten)]
+    pub info: VersionInfo,
+    pub setup: Setup,
+}
+
+impl SetupVersion {
+    pub fn new(setup: Setup, info: VersionInfo) -> Self {
+        SetupVersion {
+            timestamp: Local::now(),
+            info,
+            setup,
+        }
+    }
+
+    fn file_name(&self) -> String {
+        format!(
+            "{}.json",
+            self.timestamp.timestamp_nanos_opt().unwrap_or_default()
+        )
+    }
+}
+
+/// Versions of a single template, stored as one file per version in
+/// `SetupTemplates/<car>/<track>/.history/<template>`
+#[derive(Debug, Clone)]
+pub struct SetupHistory {
+    folder: PathBuf,
+}
+
+impl SetupHistory {
+    pub fn new(template_folder: &Path, name: &str) -> Self {
+        SetupHistory {
+            folder: template_folder.join(HISTORY_FOLDER).join(name),
+        }
+    }
+
+    /// All stored versions, oldest first
+    pub fn versions(&self) -> Vec<SetupVersion> {
+        let Ok(files) = std::fs::read_dir(&self.folder) else {
+            return Vec::new();
+        };
+
+        let mut versions: Vec<SetupVersion> = files
+            .filter_map(|f| f.ok())
+            .filter(|f| f.path().extension().is_some_and(|x| x == "json"))
+            .filter_map(|f| {
+                let data = std::fs::read_to_string(f.path()).ok()?;
+                serde_json::from_str(&data)
+                    .inspect_err(|e| error!("failed to parse setup version {:?}: {e}", f.path()))
+                    .ok()
+            })
+            .collect();
+        versions.sort_by_key(|version| version.timestamp);
+
+        versions
+    }
+
+    pub fn snapshot(&self, version: &SetupVersion) -> Result<(), SetupError> {
+        std::fs::create_dir_all(&self.folder)?;
+
+        let path = self.folder.join(version.file_name());
+        debug!("saving setup version to {:?}", path);
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(file, version)?;
+
+        Ok(())
+    }
+
+    /// Remove a version again when the change it was saved for didn't go through
+    pub fn remove(&self, version: &SetupVersion) -> Result<(), SetupError> {
+        std::fs::remove_file(self.folder.join(version.file_name()))?;
+        Ok(())
+    }
+
+    /// Snapshot the template if it differs from its latest version,
+    /// which happens for new templates and ones changed outside of the app
+    pub fn track(&self, setup: &Setup) -> Result<Option<SetupVersion>, SetupError> {
+        let versions = self.versions();
+        if versions.last().is_some_and(|latest| latest.setup == *setup) {
+            return Ok(None);
+        }
+
+        let info = if versions.is_empty() {
+            VersionInfo {
+                author: VersionInfo::external().author,
+                note: "Initial version".to_owned(),
+            }
+        } else {
+            VersionInfo::external()
+        };
+
+        let version = SetupVersion::new(setup.clone(), info);
+        self.snapshot(&version)?;
+
+        Ok(Some(version))
+    }
+}
+
+#[test]
+fn test_setup_history() -> Result<(), SetupError> {
+    let folder = crate::TestFolder::new("history");
+
+    let history = SetupHistory::new(&folder, "Race");
+    let mut setup = super::setup::setup();
+
+    let initial = history.track(&setup)?.unwrap();
+    assert_eq!(initial.info.note, "Initial version");
+    assert_eq!(history.track(&setup)?, None);
+
+    setup.advanced_setup.aero_balance.rear_wing += 1;
+    let external = history.track(&setup)?.unwrap();
+    assert_eq!(external.info, VersionInfo::external());
+
+    let versions = history.versions();
+    assert_eq!(versions.len(), 2);
+    assert_eq!(versions[1].setup, setup);
+
+    Ok(())
+}
@@ -0,0 +1,210 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::error;
+
+use super::{Setup, SetupError, SetupFile};
+
+/// Template that only stores how it differs from another template of the same track,
+/// so changes to the base carry over to all of its variants
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetupPatch {
+    /// Name of the template the patch is applied to
+    pub base: String,
+    /// Part of a setup in the ACC format, objects are merged into the base
+    /// while values and arrays replace the ones of the base
+    pub patch: Value,
+}
+
+impl SetupPatch {
+    /// Patch holding the fields of the setup that differ from the base
+    pub fn new(base: &str, base_setup: &Setup, setup: &Setup) -> Result<Self, SetupError> {
+        let patch = difference(&serde_json::to_value(base_setup)?, &serde_json::to_value(setup)?)
+            .unwrap_or_else(|| Value::Object(Map::new()));
+
+        Ok(SetupPatch {
+            base: base.to_owned(),
+            patch,
+        })
+    }
+
+    /// Full setup of the variant on top of the base
+    pub fn apply(&self, base: &Setup) -> Result<Setup, SetupError> {
+        let mut setup = serde_json::to_value(base)?;
+        merge(&mut setup, &self.patch);
+
+        Ok(serde_json::from_value(setup)?)
+    }
+}
+
+/// Resolve the setups of the templates that inherit from another one, bases first so variants
+/// of variants work. Templates with a missing base or a cycle of bases are left out.
+pub fn resolve_templates(templates: &mut BTreeMap<String, SetupFile>) {
+    let mut resolved: BTreeSet<String> = templates
+        .iter()
+        .filter(|(_, template)| template.patch.is_none())
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    loop {
+        let ready: Vec<(String, SetupPatch)> = templates
+            .iter()
+            .filter(|(name, _)| !resolved.contains(*name))
+            .filter_map(|(name, template)| Some((name.clone(), template.patch.clone()?)))
+            .filter(|(_, patch)| resolved.contains(&patch.base))
+            .collect();
+        if ready.is_empty() {
+            break;
+        }
+
+        for (name, patch) in ready {
+            match patch.apply(&templates[&patch.base].setup) {
+                Ok(setup) => {
+                    if let Some(template) = templates.get_mut(&name) {
+                        template.setup = setup;
+                    }
+                    resolved.insert(name);
+                }
+                Err(e) => {
+                    error!("failed to apply template {name} to {}: {e}", patch.base);
+                    templates.remove(&name);
+                }
+            }
+        }
+    }
+
+    templates.retain(|name, template| {
+        let Some(patch) = template.patch.as_ref().filter(|_| !resolved.contains(name)) else {
+            return true;
+        };
+        error!(
+            "template {name} is based on {}, which can't be loaded",
+            patch.base
+        );
+        false
+    });
+}
+
+fn merge(target: &mut Value, patch: &Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                match target.get_mut(key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        target.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (target, patch) => *target = patch.clone(),
+    }
+}
+
+/// Smallest patch turning the base into the value, `None` if they are the same
+fn difference(base: &Value, value: &Value) -> Option<Value> {
+    match (base, value) {
+        (Value::Object(base), Value::Object(value)) => {
+            let changes: Map<String, Value> = value
+                .iter()
+                .filter_map(|(key, value)| {
+                    let change = match base.get(key) {
+                        Some(base) => difference(base, value)?,
+                        None => value.clone(),
+                    };
+                    Some((key.clone(), change))
+                })
+                .collect();
+
+            (!changes.is_empty()).then_some(Value::Object(changes))
+        }
+        _ => (base != value).then(|| value.clone()),
+    }
+}
+
+#[test]
+fn test_setup_patch() -> Result<(), SetupError> {
+    let base = super::setup::setup();
+    let mut variant = base.clone();
+    variant.advanced_setup.aero_balance.rear_wing += 3;
+    variant.basic_setup.tyres.tyre_pressure[0] -= 1;
+
+    let patch = SetupPatch::new("Race", &base, &variant)?;
+    assert_eq!(
+        patch.patch,
+        serde_json::json!({
+            "advancedSetup": { "aeroBalance": { "rearWing": variant.advanced_setup.aero_balance.rear_wing } },
+            "basicSetup": { "tyres": { "tyrePressure": variant.basic_setup.tyres.tyre_pressure } },
+        })
+    );
+    assert_eq!(patch.apply(&base)?, variant);
+
+    // Fields that are not part of the patch follow the base
+    let mut changed = base.clone();
+    changed.basic_setup.strategy.fuel += 10;
+    let resolved = patch.apply(&changed)?;
+    assert_eq!(
+        resolved.basic_setup.strategy.fuel,
+        changed.basic_setup.strategy.fuel
+    );
+    assert_eq!(
+        resolved.advanced_setup.aero_balance.rear_wing,
+        variant.advanced_setup.aero_balance.rear_wing
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_resolve_templates() -> Result<(), SetupError> {
+    let folder = crate::TestFolder::new("inheritance");
+
+    let setup = super::setup::setup();
+    let wing = setup.advanced_setup.aero_balance.rear_wing;
+    std::fs::write(folder.join("Race.json"), serde_json::to_string(&setup)?)?;
+    let patches = [
+        (
+            "Race High Wing",
+            "Race",
+            r#"{ "advancedSetup": { "aeroBalance": { "rearWing": 12 } } }"#,
+        ),
+        (
+            "Race High Wing Wet",
+            "Race High Wing",
+            r#"{ "basicSetup": { "tyres": { "tyreCompound": 1 } } }"#,
+        ),
+        ("Orphan", "Missing", "{}"),
+        ("Loop A", "Loop B", "{}"),
+        ("Loop B", "Loop A", "{}"),
+    ];
+    for (name, base, patch) in patches {
+        let contents = format!(r#"{{ "base": "{base}", "patch": {patch} }}"#);
+        std::fs::write(folder.join(format!("{name}.json")), contents)?;
+    }
+
+    let mut templates: BTreeMap<String, SetupFile> = std::fs::read_dir(&folder)?
+        .filter_map(|f| f.ok())
+        .map(|f| SetupFile::load(&f.path()).map(|setup| (setup.name.clone(), setup)))
+        .collect::<Result<_, _>>()?;
+    resolve_templates(&mut templates);
+
+    assert_eq!(
+        templates.keys().collect::<Vec<_>>(),
+        ["Race", "Race High Wing", "Race High Wing Wet"]
+    );
+    let wet = &templates["Race High Wing Wet"].setup;
+    assert_eq!(wet.advanced_setup.aero_balance.rear_wing, 12);
+    assert_eq!(wet.basic_setup.tyres.tyre_compound, 1);
+    assert_eq!(wet.basic_setup.strategy.fuel, setup.basic_setup.strategy.fuel);
+    assert_ne!(wing, 12);
+
+    // Saving a variant keeps it a patch on top of its base
+    let mut high_wing = templates["Race High Wing"].clone();
+    high_wing.save()?;
+    let saved: SetupPatch =
+        serde_json::from_str(&std::fs::read_to_string(folder.join("Race High Wing.json"))?)?;
+    assert_eq!(saved.base, "Race");
+
+    Ok(())
+}
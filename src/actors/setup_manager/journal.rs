@@ -0,0 +1,169 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error, warn};
+
+use super::SetupError;
+
+/// Journal of every setup the app writes to the ACC setup folder with a hash of its contents.
+///
+/// Files are recorded and the journal persisted before they are written, and forgotten after they
+/// are deleted, so setups left behind by a crash can be removed on the next start. Files that are not
+/// in the journal, or that were changed since the app wrote them, are never deleted.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SetupJournal {
+    #[serde(skip)]
+    path: PathBuf,
+    /// Files changed since the journal was last persisted
+    #[serde(skip)]
+    dirty: bool,
+    files: BTreeMap<PathBuf, u64>,
+}
+
+impl SetupJournal {
+    pub fn open(path: &Path) -> Self {
+        let journal = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|data| {
+                serde_json::from_str::<SetupJournal>(&data)
+                    .inspect_err(|e| error!("failed to parse setup journal {:?}: {e}", path))
+                    .ok()
+            })
+            .unwrap_or_default();
+
+        SetupJournal {
+            path: path.to_owned(),
+            ..journal
+        }
+    }
+
+    /// Remove the setups a previous run left behind, returns the amount of deleted files
+    pub fn reconcile(&mut self) -> usize {
+        let files: Vec<PathBuf> = self.files.keys().cloned().collect();
+        let removed = files
+            .iter()
+            .filter(|path| {
+                self.remove(path)
+                    .inspect_err(|e| error!("failed to remove setup {:?}: {e}", path))
+                    .unwrap_or_default()
+            })
+            .count();
+        if let Err(e) = self.persist() {
+            error!("failed to save setup journal {:?}: {e}", self.path);
+        }
+
+        removed
+    }
+
+    /// Record a setup that is about to be written, it has to be persisted before writing the setup
+    pub fn record(&mut self, path: &Path, contents: &str) {
+        let hash = hash(contents.as_bytes());
+        if self.files.insert(path.to_owned(), hash) != Some(hash) {
+            self.dirty = true;
+        }
+    }
+
+    /// Write a recorded setup
+    pub fn write(&self, path: &Path, contents: &str) -> Result<(), SetupError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        debug!("saving setup to {:?}", path);
+        std::fs::write(path, contents)?;
+
+        Ok(())
+    }
+
+    /// Delete a setup written by the app and forget it until the next time the journal is persisted,
+    /// returns true when the file was deleted
+    pub fn remove(&mut self, path: &Path) -> Result<bool, SetupError> {
+        let Some(written) = self.files.get(path).copied() else {
+            return Ok(false);
+        };
+
+        let deleted = match std::fs::read(path) {
+            Ok(contents) if hash(&contents) == written => {
+                debug!("deleting setup: {:?}", path);
+                std::fs::remove_file(path)?;
+                true
+            }
+            Ok(_) => {
+                warn!("not deleting setup {:?}, it was changed outside of the app", path);
+                false
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+            Err(e) => return Err(e.into()),
+        };
+
+        self.files.remove(path);
+        self.dirty = true;
+
+        Ok(deleted)
+    }
+
+    /// Replace the journal on disk through a temporary file if it changed, so a crash never leaves
+    /// half of it
+    pub fn persist(&mut self) -> Result<(), SetupError> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let temporary = self.path.with_extension("tmp");
+        std::fs::write(&temporary, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&temporary, &self.path)?;
+        self.dirty = false;
+
+        Ok(())
+    }
+}
+
+/// FNV-1a hash of the file contents, stable between versions of the app
+fn hash(contents: &[u8]) -> u64 {
+    contents.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[test]
+fn test_setup_journal() -> Result<(), SetupError> {
+    let folder = crate::TestFolder::new("journal");
+
+    let path = folder.join("journal.json");
+    let setups = folder.join("Setups");
+    let written = setups.join("21c 28c Race.json");
+    let changed = setups.join("21c 28c Quali.json");
+    let foreign = setups.join("Own Setup.json");
+
+    let mut journal = SetupJournal::open(&path);
+    journal.record(&written, "race");
+    journal.record(&changed, "quali");
+    journal.persist()?;
+    journal.write(&written, "race")?;
+    journal.write(&changed, "quali")?;
+    std::fs::write(&changed, "changed in ACC")?;
+    std::fs::write(&foreign, "not written by the app")?;
+
+    // Files the app didn't write are left alone
+    assert!(!journal.remove(&foreign)?);
+    assert!(foreign.exists());
+
+    // Simulate a crash by opening the journal again without cleaning up
+    let mut journal = SetupJournal::open(&path);
+    assert_eq!(journal.files.len(), 2);
+    assert_eq!(journal.reconcile(), 1);
+
+    assert!(!written.exists());
+    assert!(changed.exists());
+    assert!(foreign.exists());
+    assert_eq!(SetupJournal::open(&path).files.len(), 0);
+
+    Ok(())
+}
@@ -4,9 +4,14 @@ use serde::{Deserialize, Serialize};
 
 use crate::telemetry::LapTime;
 
+use super::{PersonalBest, PressureBuildup, SetupError};
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SetupMeta {
     pub avg_lap: LapTime,
+    pub pressure_buildup: PressureBuildup,
+    pub personal_best: PersonalBest,
 }
 
 impl SetupMeta {
@@ -17,14 +22,14 @@ impl SetupMeta {
         serde_json::from_str(&data).unwrap_or_default()
     }
 
-    pub fn save(&self, path: &Path) {
-        if !path.exists() {
-            std::fs::create_dir_all(path).unwrap();
-        }
+    pub fn save(&self, path: &Path) -> Result<(), SetupError> {
+        std::fs::create_dir_all(path)?;
 
         let mut path = path.to_owned();
         path.push("meta.json.vapor");
-        let file = std::fs::File::create(path).unwrap();
-        serde_json::to_writer_pretty(file, self).unwrap()
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+
+        Ok(())
     }
 }
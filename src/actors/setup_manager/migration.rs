@@ -0,0 +1,82 @@
+use std::path::Path;
+
+use tracing::{debug, error, warn};
+
+use super::{library, SetupError, SetupFile, SetupInfo};
+
+/// Move the metadata of templates named like `21c 28c NAME.json` into a sidecar
+/// and rename them to `NAME.json`, returns the amount of migrated templates
+pub fn migrate_folder(folder: &Path) -> Result<usize, SetupError> {
+    let mut migrated = 0;
+
+    for entry in std::fs::read_dir(folder)?.filter_map(|f| f.ok()) {
+        let path = entry.path();
+        if path.is_dir() || path.extension().is_none_or(|x| x != "json") {
+            continue;
+        }
+        let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        if SetupInfo::path(folder, stem).exists() {
+            continue;
+        }
+
+        let (name, ambient, track) = SetupFile::parse_name(&path)?;
+        let target = folder.join(format!("{name}.json"));
+        if target != path && target.exists() {
+            warn!("not migrating {:?}, {:?} already exists", path, target);
+            continue;
+        }
+
+        debug!("migrating setup {:?} to {:?}", path, target);
+        SetupInfo::from_name(&name, ambient, track).save(folder, &name)?;
+        std::fs::rename(&path, &target)?;
+        migrated += 1;
+    }
+
+    Ok(migrated)
+}
+
+/// Migrate the templates of every car and track in the template folder
+pub fn migrate_library(template_folder: &Path) -> usize {
+    library(template_folder)
+        .iter()
+        .map(|car_track| {
+            let folder = template_folder.join(&car_track.car).join(&car_track.track);
+            migrate_folder(&folder)
+                .inspect_err(|e| error!("failed to migrate setups in {:?}: {e}", folder))
+                .unwrap_or_default()
+        })
+        .sum()
+}
+
+#[test]
+fn test_migrate_library() -> Result<(), SetupError> {
+    let folder = crate::TestFolder::new("migration");
+
+    let track = folder.join("car").join("track");
+    std::fs::create_dir_all(&track)?;
+
+    let setup = serde_json::to_string(&super::setup::setup())?;
+    std::fs::write(track.join("21c 28c RW Race.json"), &setup)?;
+    std::fs::write(track.join("5c 9c Wet Q.json"), &setup)?;
+    std::fs::write(track.join("meta.json.vapor"), "{}")?;
+
+    assert_eq!(migrate_library(&folder), 2);
+    assert!(track.join("RW Race.json").exists());
+    assert!(!track.join("21c 28c RW Race.json").exists());
+
+    let race = SetupFile::load(&track.join("RW Race.json"))?;
+    assert_eq!(race.name, "RW Race");
+    assert_eq!((race.ambient_temperature, race.track_temperature), (21, 28));
+
+    let wet = SetupFile::load(&track.join("Wet Q.json"))?;
+    assert_eq!(wet.setup_type, super::SetupType::Qualifying);
+    assert_eq!(wet.condition, super::SetupCondition::Wet);
+    assert_eq!((wet.ambient_temperature, wet.track_temperature), (5, 9));
+
+    // Migrated libraries are left alone
+    assert_eq!(migrate_library(&folder), 0);
+
+    Ok(())
+}
@@ -1,233 +1,115 @@
-use std::{collections::BTreeMap, path::PathBuf, time::Duration};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
 
 use actix::prelude::*;
-
+use chrono::{DateTime, Local};
+
+#[cfg(feature = "app")]
+mod actor;
+mod batch;
+mod best;
+mod brakes;
+mod buildup;
+mod bundle;
+mod cars;
+mod diff;
+mod fields;
+mod history;
+mod inheritance;
+mod journal;
 mod meta;
+mod migration;
+mod pressure;
 mod setup;
 mod setup_file;
-use meta::SetupMeta;
+mod sheet;
+mod state;
+#[cfg(feature = "app")]
+pub use actor::*;
+pub use batch::*;
+pub use best::*;
+pub use brakes::*;
+pub use buildup::*;
+pub use bundle::*;
+pub use cars::*;
+pub use diff::*;
+pub use fields::*;
+pub use history::*;
+pub use inheritance::*;
+pub use journal::*;
+pub use meta::SetupMeta;
+pub use migration::*;
+pub use pressure::*;
 pub use setup::*;
 pub use setup_file::*;
+pub use sheet::*;
+pub use state::*;
 use thiserror::Error;
-use tracing::{debug, error};
 
-use super::{
-    fuel_calculator::FuelMessage,
-    ui::{UiUpdate, Weather},
-    Router,
-};
+use crate::telemetry::LapWheels;
+
+use super::ui::{TrackConditions, Weather};
 
 type Car = String;
 type Track = String;
 
-#[derive(Debug, Clone)]
-#[allow(unused)]
-pub struct SetupManager {
-    pub router: Addr<Router>,
-
-    pub weather: Weather,
-    pub race_fuel: i32,
-    pub quali_fuel: i32,
-    pub telemetry_laps: i32,
-
-    pub templates: BTreeMap<String, SetupFile>,
-    pub setups: BTreeMap<String, SetupFile>,
-
-    pub setup_folder: PathBuf,
-    pub template_folder: PathBuf,
-
-    setup_scheduled: Option<SpawnHandle>,
-}
-
-impl SetupManager {
-    pub fn new(router: Addr<Router>) -> SetupManager {
-        SetupManager {
-            router,
-
-            weather: Default::default(),
-            race_fuel: Default::default(),
-            quali_fuel: Default::default(),
-            telemetry_laps: Default::default(),
-
-            templates: Default::default(),
-            setups: Default::default(),
-
-            setup_folder: Default::default(),
-            template_folder: Default::default(),
-
-            setup_scheduled: None,
-        }
-    }
-}
-
-impl Actor for SetupManager {
-    type Context = Context<Self>;
-
-    fn started(&mut self, _ctx: &mut Self::Context) {
-        self.setup_paths().unwrap();
-    }
-
-    fn stopped(&mut self, _ctx: &mut Self::Context) {
-        debug!("setup manager stopped");
-        self.cleanup_setups();
-    }
-}
-
 #[derive(Debug, Clone, Message)]
 #[rtype(result = "()")]
 pub enum SetupChange {
     Load(Car, Track),
     Weather(Weather),
+    TrackConditions(TrackConditions),
     RaceFuel(i32),
     QualiFuel(i32),
     TelemetryLaps(i32),
+    LapWheels(LapWheels),
+    /// Apply the current pressure correction to the adjusted setups, and to the templates as well
+    /// if set, saving a version with the author for each of them
+    ApplyPressureCorrection(bool, String),
+    /// Apply the current brake duct recommendation to the adjusted race setups
+    ApplyBrakeDucts,
+    /// Replace the setup of a template edited in the app and save it to the template folder
+    SaveTemplate(String, Box<Setup>, VersionInfo),
+    /// Replace the metadata of a template and save it to its sidecar
+    SaveTemplateInfo(String, SetupInfo),
+    /// Save a previous version of a template as its newest version
+    RestoreVersion(String, DateTime<Local>, VersionInfo),
+    /// Read the tracks in the template folder and the bundles in the bundle folder
+    RefreshLibrary,
+    /// Export the templates of the tracks into a bundle in the bundle folder
+    ExportBundle(Vec<CarTrack>, String),
+    /// Read a bundle and check it for conflicts with the templates, `None` closes the preview
+    PreviewBundle(Option<PathBuf>),
+    /// Import a bundle into the template folder, replacing conflicting files if set
+    ImportBundle(PathBuf, bool),
+    /// Compute the changes of a batch edit without saving them, `None` closes the preview
+    PreviewBatch(Option<BatchEdit>),
+    /// Apply a batch edit to its templates, saving a version with the author for each of them
+    ApplyBatch(BatchEdit, String),
+    /// Write adjusted setups for every temperature of the grid as well, `None` removes them
+    TemperatureGrid(Option<TemperatureGrid>),
+    /// Write a sheet of the setup to the sheet folder, listing its differences to the second one
+    ExportSheet(Box<SetupFile>, Option<Box<SetupFile>>, SheetFormat),
 }
 
-// FIXME consistency
-// i think this will all have consistency problems as it relies on timing
-// the load of the setups before the broadcast api sends over it's information
-//
-// need to make it more resilient.
-//  wait on setups to be loaded form disk?
-//  cache information in manager and apply on load from disk?
-impl Handler<SetupChange> for SetupManager {
-    type Result = ();
-
-    fn handle(&mut self, msg: SetupChange, ctx: &mut Self::Context) -> Self::Result {
-        match msg {
-            SetupChange::Weather(weather) => {
-                self.weather = weather;
-                self.adjust_weather(weather);
-            }
-            SetupChange::Load(car, track) => {
-                if let Err(err) = self.load(car, track) {
-                    error!("failed to load setups: {err}")
-                }
-
-                return;
-            }
-            SetupChange::RaceFuel(fuel) => {
-                self.race_fuel = fuel;
-
-                // Adjusts base and specific setups fuel
-                // TODO maybe add a setting for not adjusting base setups
-                self.adjust_fuel(fuel, SetupType::Base);
-                self.adjust_fuel(fuel, SetupType::Race)
-            }
-            SetupChange::QualiFuel(fuel) => {
-                self.quali_fuel = fuel;
-
-                self.adjust_fuel(fuel, SetupType::Base);
-                self.adjust_fuel(fuel, SetupType::Qualifying);
-            }
-            SetupChange::TelemetryLaps(laps) => {
-                self.telemetry_laps = laps;
-                self.adjust_telemetry_laps(laps);
-            }
-        };
-
-        ctx.notify(CommitChanges::Schedule);
-    }
-}
-
-impl SetupManager {
-    fn setup_paths(&mut self) -> Result<(), SetupError> {
-        #[cfg(windows)]
-        let documents = known_folders::get_known_folder_path(known_folders::KnownFolder::Documents).unwrap();
-        #[cfg(not(windows))]
-        let documents = PathBuf::from("./setups");
-
-        self.setup_folder = documents.clone();
-        self.setup_folder.push("Assetto Corsa Competizione");
-        self.setup_folder.push("Setups");
-
-        self.template_folder = documents;
-        self.template_folder.push(crate::PROGRAM_NAME);
-        self.template_folder.push("SetupTemplates");
-
-        std::fs::create_dir_all(&self.template_folder)?;
-
-        Ok(())
-    }
-
-    fn load(&mut self, car: Car, track: Track) -> Result<(), SetupError> {
-        let template_folder = self.template_folder.join(&car).join(&track);
-        std::fs::create_dir_all(&template_folder)?;
-
-        self.cleanup_setups();
-
-        let setups = std::fs::read_dir(&template_folder).map_err(|_| SetupError::NoSetups)?;
-
-        self.templates = setups
-            .into_iter()
-            .filter_map(|f| f.ok())
-            .filter(|f| (!f.path().is_dir()) && f.path().extension().is_some_and(|x| x == "json"))
-            .map(|f| SetupFile::load(&f.path()))
-            .map(|setup| setup.and_then(|setup| Ok((setup.name.clone(), setup))))
-            .collect::<Result<BTreeMap<String, SetupFile>, SetupError>>()?;
-
-        self.router
-            .do_send(UiUpdate::SetupTemplates(self.templates.clone()));
-
-        self.setups = self
-            .templates
-            .iter()
-            .map(|setup| {
-                let (name, setup) = setup;
-                let mut setup = setup.clone();
-                setup.path = self.setup_folder.join(&car).join(&track);
-
-                (name.clone(), setup)
-            })
-            .collect();
-
-        // Send fuel per lap of whatever setup we get to fuelcalculator
-        // to at least have some value in it
-        if let Some((_, setup)) = self.setups.iter().next() {
-            self.router.do_send(FuelMessage::FuelPerLap(
-                setup.setup.basic_setup.strategy.fuel_per_lap,
-            ));
-        }
-
-        // Read the avg lap time from meta file and send to fuelcalculator
-        // to have a starting value to work with
-        let meta = SetupMeta::read(&template_folder);
-        debug!("loaded meta: {meta:?}");
-        self.router.do_send(FuelMessage::AvgLapTime(meta.avg_lap));
-
-        Ok(())
-    }
-
-    fn adjust_weather(&mut self, weather: Weather) {
-        self.cleanup_setups();
-        self.setups
-            .iter_mut()
-            .for_each(|(_, setup)| setup.adjust_weather(&weather));
-
-        self.router.do_send(UiUpdate::SetupAdjusted(self.setups.clone()));
-    }
-
-    fn adjust_fuel(&mut self, fuel: i32, setup_type: SetupType) {
-        self.setups
-            .iter_mut()
-            .filter(|(_, setup)| setup.setup_type == setup_type)
-            .for_each(|(_, setup)| setup.adjust_fuel(fuel))
-    }
-
-    fn adjust_telemetry_laps(&mut self, laps: i32) {
-        self.setups
-            .iter_mut()
-            .for_each(|(_, setup)| setup.adjust_telemetry_laps(laps));
-    }
-
-    fn cleanup_setups(&self) {
-        self.setups.iter().for_each(|(_name, setup)| {
-            setup.delete().unwrap_or_else(|e| {
-                error!("failed to delete setup: {e:?}");
-                ()
-            });
+/// Templates in the template folder of a track by their name,
+/// with the ones based on another template resolved
+pub fn read_templates(template_folder: &Path) -> Result<BTreeMap<String, SetupFile>, SetupError> {
+    let setups = std::fs::read_dir(template_folder).map_err(|_| SetupError::NoSetups)?;
+
+    setups
+        .into_iter()
+        .filter_map(|f| f.ok())
+        .filter(|f| (!f.path().is_dir()) && f.path().extension().is_some_and(|x| x == "json"))
+        .map(|f| SetupFile::load(&f.path()))
+        .map(|setup| setup.map(|setup| (setup.name.clone(), setup)))
+        .collect::<Result<_, _>>()
+        .map(|mut templates| {
+            resolve_templates(&mut templates);
+            templates
         })
-    }
 }
 
 #[derive(Error, Debug)]
@@ -240,31 +122,8 @@ pub enum SetupError {
     ParsePathError,
     #[error("failed to parse setup file")]
     SerdeError(#[from] serde_json::Error),
-}
-
-#[derive(Debug, Clone, Message)]
-#[rtype(result = "()")]
-enum CommitChanges {
-    Schedule,
-    Handle,
-}
-
-impl Handler<CommitChanges> for SetupManager {
-    type Result = ();
-
-    fn handle(&mut self, msg: CommitChanges, ctx: &mut Self::Context) -> Self::Result {
-        match msg {
-            CommitChanges::Schedule => {
-                if let Some(handle) = self.setup_scheduled {
-                    ctx.cancel_future(handle);
-                }
-
-                ctx.notify_later(CommitChanges::Handle, Duration::from_millis(500));
-            }
-            CommitChanges::Handle => {
-                self.setups.iter_mut().for_each(|(_, s)| s.save());
-                self.router.do_send(UiUpdate::SetupAdjusted(self.setups.clone()));
-            }
-        }
-    }
+    #[error("invalid setup: {0}")]
+    InvalidSetup(String),
+    #[error("invalid setup bundle: {0}")]
+    InvalidBundle(String),
 }
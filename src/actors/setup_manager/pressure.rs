@@ -0,0 +1,155 @@
+use crate::telemetry::{shm::Wheels, LapWheels};
+
+use super::{Parameter, Tyres};
+
+/// Amount of most recent laps the measured pressures are averaged over
+const CORRECTION_LAPS: usize = 3;
+/// Minimum amount of laps needed before suggesting a correction
+const MIN_CORRECTION_LAPS: usize = 2;
+
+/// Window the hot tyre pressures should be in, in psi
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PressureTarget {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl PressureTarget {
+    pub fn for_compound(compound: i32) -> Self {
+        if compound == Tyres::WET_COMPOUND {
+            PressureTarget { min: 30.0, max: 31.0 }
+        } else {
+            PressureTarget { min: 26.2, max: 27.0 }
+        }
+    }
+
+    pub fn ideal(&self) -> f32 {
+        (self.min + self.max) / 2.0
+    }
+
+    /// Clicks of cold pressure needed to bring the measured hot pressure into the window,
+    /// assumes one click of cold pressure moves the hot pressure by the same amount
+    pub fn clicks(&self, measured: f32, pressure: &Parameter) -> i32 {
+        if measured >= self.min && measured <= self.max {
+            0
+        } else {
+            ((self.ideal() - measured) / pressure.step).round() as i32
+        }
+    }
+}
+
+/// Per corner correction of the cold pressures computed from the measured hot pressures
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PressureCorrection {
+    /// Tyre compound the laps were driven on
+    pub compound: i32,
+    /// Amount of laps the measurement is based on
+    pub laps: usize,
+    /// Average hot pressures over the laps
+    pub measured: Wheels<f32>,
+    /// Clicks to add to the cold pressures in FL, FR, RL, RR order
+    pub clicks: [i32; 4],
+}
+
+impl PressureCorrection {
+    /// Compute the correction from the most recent laps driven on the same compound as the last lap,
+    /// in clicks of the tyre pressure parameter of the car
+    pub fn from_laps(laps: &[LapWheels], pressure: &Parameter) -> Option<Self> {
+        let last = laps.last()?;
+        let laps: Vec<&Wheels<f32>> = laps
+            .iter()
+            .rev()
+            .filter(|lap| lap.rain_tyres == last.rain_tyres)
+            .map(|lap| &lap.tyre_pressure.avg)
+            .filter(|p| p.front_left > 0.0 && p.front_right > 0.0 && p.rear_left > 0.0 && p.rear_right > 0.0)
+            .take(CORRECTION_LAPS)
+            .collect();
+
+        if laps.len() < MIN_CORRECTION_LAPS {
+            return None;
+        }
+
+        let count = laps.len() as f32;
+        let measured: Wheels<f32> = laps
+            .iter()
+            .fold((0.0, 0.0, 0.0, 0.0), |mut sum, p| {
+                sum.0 += p.front_left;
+                sum.1 += p.front_right;
+                sum.2 += p.rear_left;
+                sum.3 += p.rear_right;
+
+                sum
+            })
+            .into();
+        let measured = measured / (count, count, count, count).into();
+
+        let compound = if last.rain_tyres {
+            Tyres::WET_COMPOUND
+        } else {
+            Tyres::DRY_COMPOUND
+        };
+        let target = PressureTarget::for_compound(compound);
+
+        Some(PressureCorrection {
+            compound,
+            laps: laps.len(),
+            clicks: [
+                target.clicks(measured.front_left, pressure),
+                target.clicks(measured.front_right, pressure),
+                target.clicks(measured.rear_left, pressure),
+                target.clicks(measured.rear_right, pressure),
+            ],
+            measured,
+        })
+    }
+
+    /// Correction does not change any pressure
+    pub fn is_empty(&self) -> bool {
+        self.clicks.iter().all(|c| *c == 0)
+    }
+}
+
+#[cfg(test)]
+fn lap(pressure: (f32, f32, f32, f32), rain_tyres: bool) -> LapWheels {
+    let mut lap = LapWheels {
+        rain_tyres,
+        ..Default::default()
+    };
+    lap.tyre_pressure.avg = pressure.into();
+    lap
+}
+
+#[test]
+fn test_pressure_correction() {
+    let pressure = super::CarParameters::default().tyre_pressure;
+    assert_eq!(
+        None,
+        PressureCorrection::from_laps(&[lap((27.5, 27.5, 27.5, 27.5), false)], &pressure)
+    );
+
+    let laps = [
+        lap((27.4, 26.6, 26.0, 26.6), false),
+        lap((27.6, 26.6, 25.8, 0.0), false),
+        lap((27.6, 26.6, 26.2, 26.6), false),
+    ];
+    let correction = PressureCorrection::from_laps(&laps, &pressure).unwrap();
+
+    assert_eq!(correction.compound, Tyres::DRY_COMPOUND);
+    assert_eq!(correction.laps, 2);
+    assert_eq!(correction.clicks, [-9, 0, 5, 0]);
+    assert!(!correction.is_empty());
+}
+
+#[test]
+fn test_pressure_correction_compound() {
+    let laps = [
+        lap((26.6, 26.6, 26.6, 26.6), false),
+        lap((29.5, 29.5, 29.5, 29.5), true),
+        lap((29.5, 29.5, 29.5, 29.5), true),
+    ];
+    let pressure = super::CarParameters::default().tyre_pressure;
+    let correction = PressureCorrection::from_laps(&laps, &pressure).unwrap();
+
+    assert_eq!(correction.compound, Tyres::WET_COMPOUND);
+    assert_eq!(correction.clicks, [10, 10, 10, 10]);
+}
@@ -28,6 +28,17 @@ pub struct Tyres {
     pub tyre_pressure: [i32; 4],
 }
 
+impl Tyres {
+    /// Compound index ACC uses for dry tyres
+    pub const DRY_COMPOUND: i32 = 0;
+    /// Compound index ACC uses for rain tyres
+    pub const WET_COMPOUND: i32 = 1;
+
+    pub fn is_wet(&self) -> bool {
+        self.tyre_compound == Self::WET_COMPOUND
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub struct Alignment {
     pub camber: [i32; 4],
@@ -165,7 +176,7 @@ fn test_setup_serialize() {
 }
 
 #[cfg(test)]
-fn setup() -> Setup {
+pub(crate) fn setup() -> Setup {
     Setup {
         car_name: "mercedes_amg_gt3_evo".to_string(),
         basic_setup: BasicSetup {
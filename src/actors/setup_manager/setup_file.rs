@@ -5,11 +5,78 @@ use std::{
 };
 
 use regex::Regex;
-use tracing::debug;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+use tracing::{debug, warn};
 
-use crate::actors::ui::Weather;
+use crate::{
+    actors::ui::{TrackConditions, Weather},
+    telemetry::shm::{RainIntensity, TrackGripStatus},
+};
 
-use super::{Setup, SetupError};
+use super::{CarParameters, Parameter, Setup, SetupError, SetupPatch, Tyres};
+
+/// Pressure clicks per °C of track temperature difference on rain tyres
+const WET_CLICKS_PER_DEGREE: f32 = 0.5;
+/// Reference temperatures of templates that don't have any
+const DEFAULT_AMBIENT_TEMPERATURE: u8 = 20;
+const DEFAULT_TRACK_TEMPERATURE: u8 = 26;
+/// Extension of the metadata sidecar stored next to every template
+pub const INFO_EXTENSION: &str = "info.json.vapor";
+
+/// Metadata of a template, stored in `<template>.info.json.vapor` next to it
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SetupInfo {
+    /// Temperatures the template was made for
+    pub ambient_temperature: u8,
+    pub track_temperature: u8,
+    pub setup_type: SetupType,
+    pub condition: SetupCondition,
+    pub notes: String,
+    pub author: String,
+}
+
+impl SetupInfo {
+    /// Guess the metadata of a template from its name, for templates without a sidecar
+    pub fn from_name(name: &str, ambient_temperature: Option<u8>, track_temperature: Option<u8>) -> Self {
+        if ambient_temperature.is_none() || track_temperature.is_none() {
+            warn!("no temperatures in setup name {name}, using the defaults");
+        }
+
+        SetupInfo {
+            ambient_temperature: ambient_temperature.unwrap_or(DEFAULT_AMBIENT_TEMPERATURE),
+            track_temperature: track_temperature.unwrap_or(DEFAULT_TRACK_TEMPERATURE),
+            setup_type: SetupType::from_str(name).unwrap_or_default(),
+            condition: SetupCondition::from_str(name).unwrap_or_default(),
+            notes: String::new(),
+            author: String::new(),
+        }
+    }
+
+    pub fn path(folder: &Path, name: &str) -> PathBuf {
+        folder.join(format!("{name}.{INFO_EXTENSION}"))
+    }
+
+    pub fn read(folder: &Path, name: &str) -> Result<Option<Self>, SetupError> {
+        let path = Self::path(folder, name);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let data = std::fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&data)?))
+    }
+
+    pub fn save(&self, folder: &Path, name: &str) -> Result<(), SetupError> {
+        let path = Self::path(folder, name);
+        debug!("saving setup info to {:?}", path);
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+
+        Ok(())
+    }
+}
 
 #[derive(Debug, Clone)]
 #[allow(unused)]
@@ -20,31 +87,65 @@ pub struct SetupFile {
     pub ambient_temperature: u8,
     pub track_temperature: u8,
     pub setup_type: SetupType,
+    pub condition: SetupCondition,
+    pub notes: String,
+    pub author: String,
+    /// Templates are saved as `<name>.json` with a metadata sidecar, adjusted setups are
+    /// named after their temperatures so they can be told apart in ACC
+    pub template: bool,
+    /// Base and changes of a template that inherits from another one,
+    /// `setup` holds the resolved setup
+    pub patch: Option<SetupPatch>,
 
     pub setup: Setup,
 }
 
 impl SetupFile {
+    /// Load a template and its metadata sidecar,
+    /// falling back to the metadata in the file name for templates without one.
+    ///
+    /// Templates based on another one are loaded with a default setup until they are resolved.
     pub fn load(path: &Path) -> Result<Self, SetupError> {
         debug!("trying to load setup from {:?}", path);
         let data = std::fs::read_to_string(path)?;
-        let setup: Setup = serde_json::from_str(&data)?;
-
-        let (name, ambient_temperature, track_temperature) = Self::parse_name(path)?;
-        let setup_type = SetupType::from_str(&name)?;
-        let path = path.parent().unwrap().to_owned();
+        let (setup, patch) = match serde_json::from_str::<SetupPatch>(&data) {
+            Ok(patch) => (Setup::default(), Some(patch)),
+            Err(_) => (serde_json::from_str(&data)?, None),
+        };
+
+        let folder = path.parent().ok_or(SetupError::ParsePathError)?.to_owned();
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or(SetupError::ParsePathError)?;
+
+        let (name, info) = match SetupInfo::read(&folder, stem)? {
+            Some(info) => (stem.to_owned(), info),
+            None => {
+                let (name, ambient, track) = Self::parse_name(path)?;
+                let info = SetupInfo::from_name(&name, ambient, track);
+                (name, info)
+            }
+        };
 
         Ok(SetupFile {
-            ambient_temperature,
-            track_temperature,
-            setup_type,
+            ambient_temperature: info.ambient_temperature,
+            track_temperature: info.track_temperature,
+            setup_type: info.setup_type,
+            condition: info.condition,
+            notes: info.notes,
+            author: info.author,
+            template: true,
+            patch,
             name,
-            path,
+            path: folder,
             setup,
         })
     }
 
-    pub fn parse_name(path: &Path) -> Result<(String, u8, u8), SetupError> {
+    /// Split a legacy file name like `21c 28c NAME.json` into the name and its temperatures,
+    /// names without temperatures are returned as they are
+    pub fn parse_name(path: &Path) -> Result<(String, Option<u8>, Option<u8>), SetupError> {
         let file_name = path
             .file_name()
             .ok_or(SetupError::ParsePathError)?
@@ -52,19 +153,45 @@ impl SetupFile {
             .ok_or(SetupError::ParsePathError)?
             .strip_suffix(".json")
             .ok_or(SetupError::ParsePathError)?
-            .to_owned();
+            .trim();
 
-        // TODO improve this to be more lax in parsing and guessing the air/track temperature
-        // 21c 26c NAME
-        // 012345678
-        let ambient_temperature = (&file_name[0..2]).parse().unwrap();
-        let track_temperature = (&file_name[4..6]).parse().unwrap();
-        let name = file_name[8..].to_owned();
+        let name_re = Regex::new(r"(?i)^(\d{1,2}) ?c[ _-]+(?:(\d{1,2}) ?c[ _-]+)?(.+)$").unwrap();
+        let Some(captures) = name_re.captures(file_name) else {
+            return Ok((file_name.to_owned(), None, None));
+        };
 
-        Ok((name, ambient_temperature, track_temperature))
+        let temperature = |i: usize| captures.get(i).and_then(|t| t.as_str().parse().ok());
+        let name = captures[3].trim().to_owned();
+
+        Ok((name, temperature(1), temperature(2)))
+    }
+
+    /// Metadata of the setup as stored in its sidecar
+    pub fn info(&self) -> SetupInfo {
+        SetupInfo {
+            ambient_temperature: self.ambient_temperature,
+            track_temperature: self.track_temperature,
+            setup_type: self.setup_type,
+            condition: self.condition,
+            notes: self.notes.clone(),
+            author: self.author.clone(),
+        }
+    }
+
+    pub fn set_info(&mut self, info: SetupInfo) {
+        self.ambient_temperature = info.ambient_temperature;
+        self.track_temperature = info.track_temperature;
+        self.setup_type = info.setup_type;
+        self.condition = info.condition;
+        self.notes = info.notes;
+        self.author = info.author;
     }
 
     fn file_name(&self) -> PathBuf {
+        if self.template {
+            return PathBuf::from(format!("{}.json", self.name));
+        }
+
         PathBuf::from(format!(
             "{}c {}c {}.json",
             self.ambient_temperature, self.track_temperature, self.name
@@ -75,57 +202,113 @@ impl SetupFile {
         self.path.join(self.file_name())
     }
 
-    pub fn save(&mut self) {
-        if !self.path.exists() {
-            std::fs::create_dir_all(&self.path).unwrap();
-        }
+    pub fn save(&mut self) -> Result<(), SetupError> {
+        std::fs::create_dir_all(&self.path)?;
 
         let path = self.path_with_name();
         debug!("saving setup to {:?}", path);
-        let file = File::create(path).unwrap();
-        serde_json::to_writer_pretty(file, &self.setup).unwrap()
+        std::fs::write(path, self.contents()?)?;
+
+        if self.template {
+            self.info().save(&self.path, &self.name)?;
+        }
+
+        Ok(())
     }
 
-    pub fn delete(&self) -> std::io::Result<()> {
-        let path = self.path_with_name();
-        debug!("deleting setup: {:?}", path);
-        std::fs::remove_file(path)
+    /// Json the setup is saved as, variants only store their changes to the base
+    pub fn contents(&self) -> Result<String, SetupError> {
+        Ok(match self.patch.as_ref().filter(|_| self.template) {
+            Some(patch) => serde_json::to_string_pretty(patch)?,
+            None => serde_json::to_string_pretty(&self.setup)?,
+        })
     }
 
     pub fn adjust_weather(&mut self, weather: &Weather) {
-        let diff = self.ambient_temperature as i8 - weather.ambient_temp as i8;
+        let ambient_diff = self.ambient_temperature as i32 - weather.ambient_temp as i32;
+        let track_diff = self.track_temperature as i32 - weather.track_temp as i32;
         debug!(
-            "adjusting pressure to temp {:?} by {:?} clicks",
-            weather.ambient_temp, diff
+            "adjusting pressure to temp {:?}/{:?} by {:?}/{:?} degrees",
+            weather.ambient_temp, weather.track_temp, ambient_diff, track_diff
         );
         self.ambient_temperature = weather.ambient_temp;
         self.track_temperature = weather.track_temp;
 
+        let pressure = &self.parameters().tyre_pressure;
+        adjust_pressure(
+            &mut self.setup.basic_setup.tyres,
+            pressure,
+            ambient_diff,
+            track_diff,
+        );
         self.setup
             .basic_setup
-            .tyres
-            .tyre_pressure
+            .strategy
+            .pit_strategy
             .iter_mut()
-            .for_each(|i| *i += diff as i32);
+            .for_each(|s| adjust_pressure(&mut s.tyres, pressure, ambient_diff, track_diff));
+    }
+
+    /// Restore the reference temperatures and tyre pressures from the template,
+    /// so the next weather adjustment starts from the original setup again
+    pub fn reset_weather(&mut self, template: &SetupFile) {
+        self.ambient_temperature = template.ambient_temperature;
+        self.track_temperature = template.track_temperature;
+
+        self.setup.basic_setup.tyres.tyre_pressure = template.setup.basic_setup.tyres.tyre_pressure;
         self.setup
             .basic_setup
             .strategy
             .pit_strategy
             .iter_mut()
-            .for_each(|s| s.tyres.tyre_pressure.iter_mut().for_each(|i| *i += diff as i32));
+            .zip(template.setup.basic_setup.strategy.pit_strategy.iter())
+            .for_each(|(s, t)| s.tyres.tyre_pressure = t.tyres.tyre_pressure);
+    }
+
+    /// Shift the pressures of every tyre set using the compound by a per corner amount of clicks
+    pub fn adjust_pressure_clicks(&mut self, compound: i32, clicks: &[i32; 4]) {
+        let pressure = &self.parameters().tyre_pressure;
+        let basic_setup = &mut self.setup.basic_setup;
+        std::iter::once(&mut basic_setup.tyres)
+            .chain(basic_setup.strategy.pit_strategy.iter_mut().map(|s| &mut s.tyres))
+            .filter(|tyres| tyres.tyre_compound == compound)
+            .for_each(|tyres| {
+                tyres
+                    .tyre_pressure
+                    .iter_mut()
+                    .zip(clicks)
+                    .for_each(|(p, clicks)| *p = pressure.clamp(*p + clicks))
+            });
     }
 
     pub fn adjust_fuel(&mut self, fuel: i32) {
-        self.setup.basic_setup.strategy.fuel = fuel - 2;
+        self.setup.basic_setup.strategy.fuel = self.parameters().fuel.clamp(fuel - 2);
     }
 
     pub fn adjust_telemetry_laps(&mut self, laps: i32) {
         self.setup.basic_setup.electronics.telemetry_laps = laps;
     }
+
+    /// Open or close the front and rear brake ducts by an amount of clicks
+    pub fn adjust_brake_ducts(&mut self, clicks: &[i32; 2]) {
+        let brake_duct = &self.parameters().brake_duct;
+        self.setup
+            .advanced_setup
+            .aero_balance
+            .brake_duct
+            .iter_mut()
+            .zip(clicks)
+            .for_each(|(duct, clicks)| *duct = brake_duct.clamp(*duct + clicks));
+    }
+
+    /// Physical units and legal ranges of the car the setup is for
+    pub fn parameters(&self) -> &'static CarParameters {
+        CarParameters::get(&self.setup.car_name)
+    }
 }
 
 #[allow(unused)]
-#[derive(Default, Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[derive(Default, Debug, Display, Copy, Clone, PartialEq, PartialOrd, EnumIter, Serialize, Deserialize)]
 pub enum SetupType {
     #[default]
     Base,
@@ -137,8 +320,8 @@ impl std::str::FromStr for SetupType {
     type Err = SetupError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let quali_re = Regex::new(r" (Q|Quali)( |$)").unwrap();
-        let race_re = Regex::new(r" (R|Race)( |$)").unwrap();
+        let quali_re = Regex::new(r"(^| )(Q|Quali)( |$)").unwrap();
+        let race_re = Regex::new(r"(^| )(R|Race)( |$)").unwrap();
 
         Ok(if quali_re.is_match(s) {
             SetupType::Qualifying
@@ -150,6 +333,70 @@ impl std::str::FromStr for SetupType {
     }
 }
 
+/// Shift the pressures of a tyre set by the amount of clicks needed for the temperature change.
+///
+/// Dry tyres follow the ambient temperature at one click per degree, rain tyres
+/// run much cooler and mostly follow the track surface, so they move at a lower rate.
+/// The result is kept within the pressure range of the car if it is known.
+fn adjust_pressure(tyres: &mut Tyres, pressure: &Parameter, ambient_diff: i32, track_diff: i32) {
+    let clicks = if tyres.is_wet() {
+        (track_diff as f32 * WET_CLICKS_PER_DEGREE).round() as i32
+    } else {
+        ambient_diff
+    };
+
+    tyres
+        .tyre_pressure
+        .iter_mut()
+        .for_each(|i| *i = pressure.clamp(*i + clicks));
+}
+
+/// Track condition a setup is meant for
+#[derive(Default, Debug, Display, Copy, Clone, PartialEq, PartialOrd, EnumIter, Serialize, Deserialize)]
+pub enum SetupCondition {
+    #[default]
+    Dry,
+    Intermediate,
+    Wet,
+}
+
+impl SetupCondition {
+    /// Pick the setup condition that fits the current track state best
+    pub fn from_conditions(weather: &Weather, conditions: &TrackConditions) -> Self {
+        // wetness is reported in tenths by the broadcast api
+        if conditions.grip_status >= TrackGripStatus::Wet
+            || conditions.rain_intensity >= RainIntensity::MediumRain
+            || weather.wetness >= 5
+        {
+            SetupCondition::Wet
+        } else if conditions.grip_status >= TrackGripStatus::Damp
+            || conditions.rain_intensity >= RainIntensity::LightRain
+            || weather.wetness >= 2
+        {
+            SetupCondition::Intermediate
+        } else {
+            SetupCondition::Dry
+        }
+    }
+}
+
+impl std::str::FromStr for SetupCondition {
+    type Err = SetupError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let wet_re = Regex::new(r"(^| )(W|Wet|Rain)( |$)").unwrap();
+        let inter_re = Regex::new(r"(^| )(I|Inter|Damp)( |$)").unwrap();
+
+        Ok(if wet_re.is_match(s) {
+            SetupCondition::Wet
+        } else if inter_re.is_match(s) {
+            SetupCondition::Intermediate
+        } else {
+            SetupCondition::Dry
+        })
+    }
+}
+
 #[test]
 #[cfg(test)]
 fn test_setup_type() -> Result<(), SetupError> {
@@ -160,6 +407,7 @@ fn test_setup_type() -> Result<(), SetupError> {
     assert_eq!(SetupType::Race, SetupType::from_str("20c 23c RW Race")?);
     assert_eq!(SetupType::Race, SetupType::from_str("20c 23c RW R Test")?);
     assert_eq!(SetupType::Base, SetupType::from_str("20c 23c RW Test")?);
+    assert_eq!(SetupType::Race, SetupType::from_str("Race")?);
 
     Ok(())
 }
@@ -167,12 +415,27 @@ fn test_setup_type() -> Result<(), SetupError> {
 #[test]
 #[cfg(test)]
 fn test_parse_name() -> Result<(), SetupError> {
-    let path = PathBuf::from("test\\21c 28c TEST Race Setup.json");
+    let path = PathBuf::from("test").join("21c 28c TEST Race Setup.json");
 
     let (name, ambient, track) = SetupFile::parse_name(&path)?;
     assert_eq!(name, "TEST Race Setup");
-    assert_eq!(ambient, 21);
-    assert_eq!(track, 28);
+    assert_eq!(ambient, Some(21));
+    assert_eq!(track, Some(28));
+
+    let (name, ambient, track) = SetupFile::parse_name(&PathBuf::from("5c 12C Wet Race.json"))?;
+    assert_eq!(name, "Wet Race");
+    assert_eq!(ambient, Some(5));
+    assert_eq!(track, Some(12));
+
+    let (name, ambient, track) = SetupFile::parse_name(&PathBuf::from("21c Race.json"))?;
+    assert_eq!(name, "Race");
+    assert_eq!(ambient, Some(21));
+    assert_eq!(track, None);
+
+    let (name, ambient, track) = SetupFile::parse_name(&PathBuf::from("Monza Q.json"))?;
+    assert_eq!(name, "Monza Q");
+    assert_eq!(ambient, None);
+    assert_eq!(track, None);
 
     Ok(())
 }
@@ -186,9 +449,118 @@ fn test_file_name() {
         ambient_temperature: 24,
         track_temperature: 31,
         setup_type: SetupType::Race,
+        condition: SetupCondition::Dry,
+        notes: String::new(),
+        author: String::new(),
+        template: false,
+        patch: None,
         setup: Setup::default(),
     };
 
     let file_name = setup.file_name();
-    assert_eq!(file_name, PathBuf::from("24c 31c TEST Race Setup.json"))
+    assert_eq!(file_name, PathBuf::from("24c 31c TEST Race Setup.json"));
+
+    let template = SetupFile {
+        template: true,
+        ..setup
+    };
+    assert_eq!(template.file_name(), PathBuf::from("TEST Race Setup.json"));
+}
+
+#[test]
+#[cfg(test)]
+fn test_setup_condition() -> Result<(), SetupError> {
+    assert_eq!(SetupCondition::Wet, SetupCondition::from_str("RW Wet R")?);
+    assert_eq!(SetupCondition::Wet, SetupCondition::from_str("W Race")?);
+    assert_eq!(
+        SetupCondition::Intermediate,
+        SetupCondition::from_str("RW Inter Q")?
+    );
+    assert_eq!(SetupCondition::Dry, SetupCondition::from_str("RW Wing Race")?);
+
+    let mut weather = Weather::default();
+    let mut conditions = TrackConditions::default();
+    assert_eq!(
+        SetupCondition::Dry,
+        SetupCondition::from_conditions(&weather, &conditions)
+    );
+
+    conditions.grip_status = TrackGripStatus::Damp;
+    assert_eq!(
+        SetupCondition::Intermediate,
+        SetupCondition::from_conditions(&weather, &conditions)
+    );
+
+    weather.wetness = 6;
+    assert_eq!(
+        SetupCondition::Wet,
+        SetupCondition::from_conditions(&weather, &conditions)
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(test)]
+fn test_adjust_weather_wet() {
+    let mut setup = SetupFile {
+        name: "TEST Wet Race".to_owned(),
+        path: PathBuf::new(),
+        ambient_temperature: 20,
+        track_temperature: 24,
+        setup_type: SetupType::Race,
+        condition: SetupCondition::Wet,
+        notes: String::new(),
+        author: String::new(),
+        template: false,
+        patch: None,
+        setup: Setup::default(),
+    };
+    setup.setup.basic_setup.tyres.tyre_compound = 1;
+    // Rain tyres run around 30 psi, above the dry pressures
+    setup.setup.basic_setup.tyres.tyre_pressure = [97, 97, 97, 97];
+    let template = setup.clone();
+
+    let weather = Weather {
+        ambient_temp: 14,
+        track_temp: 18,
+        ..Default::default()
+    };
+    setup.adjust_weather(&weather);
+    assert_eq!(setup.setup.basic_setup.tyres.tyre_pressure, [100, 100, 100, 100]);
+
+    setup.reset_weather(&template);
+    assert_eq!(setup.setup.basic_setup.tyres.tyre_pressure, [97, 97, 97, 97]);
+    assert_eq!(setup.ambient_temperature, 20);
+}
+
+#[test]
+#[cfg(test)]
+fn test_load_info() -> Result<(), SetupError> {
+    let folder = crate::TestFolder::new("setup-info");
+
+    let path = folder.join("Race.json");
+    std::fs::write(&path, serde_json::to_string(&super::setup::setup())?)?;
+
+    // Without a sidecar the defaults and the name are used
+    let mut setup = SetupFile::load(&path)?;
+    assert_eq!(setup.name, "Race");
+    assert_eq!(setup.setup_type, SetupType::Race);
+    assert_eq!(setup.ambient_temperature, DEFAULT_AMBIENT_TEMPERATURE);
+
+    setup.set_info(SetupInfo {
+        ambient_temperature: 14,
+        track_temperature: 19,
+        setup_type: SetupType::Qualifying,
+        condition: SetupCondition::Wet,
+        notes: "Low downforce".to_owned(),
+        author: "Author".to_owned(),
+    });
+    setup.save()?;
+
+    let loaded = SetupFile::load(&path)?;
+    assert_eq!(loaded.info(), setup.info());
+    assert!(SetupInfo::path(&folder, "Race").exists());
+
+    Ok(())
 }
@@ -0,0 +1,270 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+use strum::{Display, EnumIter};
+
+use super::{diff, fields, SetupError, SetupField, SetupFile};
+
+/// File format of a setup sheet
+#[derive(Debug, Default, Display, Clone, Copy, PartialEq, EnumIter)]
+pub enum SheetFormat {
+    #[default]
+    Html,
+    Markdown,
+}
+
+impl SheetFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SheetFormat::Html => "html",
+            SheetFormat::Markdown => "md",
+        }
+    }
+}
+
+/// Printable overview of a setup with every value in physical units, as required by some leagues
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetupSheet {
+    pub title: String,
+    pub sections: Vec<SheetSection>,
+}
+
+/// Table of a setup sheet, e.g. the tyres or the differences to another setup
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetSection {
+    pub title: String,
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl SetupSheet {
+    /// Sheet of the setup, listing the values that differ from the compared setup at the end
+    pub fn new(file: &SetupFile, compare: Option<&SetupFile>) -> Self {
+        let setup = &file.setup;
+        let track = file
+            .path
+            .file_name()
+            .map(|track| track.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let strategy = &setup.basic_setup.strategy;
+
+        let mut overview = vec![
+            row(["Car", &setup.car_name]),
+            row(["Track", &track]),
+            row(["Type", &file.setup_type.to_string()]),
+            row(["Condition", &file.condition.to_string()]),
+            row([
+                "Temperatures",
+                &format!(
+                    "{}°C ambient, {}°C track",
+                    file.ambient_temperature, file.track_temperature
+                ),
+            ]),
+            row(["Fuel", &format!("{} l", strategy.fuel)]),
+            row(["Fuel per Lap", &format!("{:.2} l", strategy.fuel_per_lap)]),
+            row(["Pit Stops", &strategy.n_pit_stops.to_string()]),
+        ];
+        if !file.author.is_empty() {
+            overview.push(row(["Author", &file.author]));
+        }
+        if !file.notes.is_empty() {
+            overview.push(row(["Notes", &file.notes]));
+        }
+
+        let mut sections = vec![SheetSection {
+            title: "Setup".to_owned(),
+            header: row(["", ""]),
+            rows: overview,
+        }];
+
+        // Values are grouped by their section in the order of the setup json
+        for field in fields(setup) {
+            let value = format_field(&field);
+            match sections.last_mut() {
+                Some(section) if section.title == field.section => {
+                    section.rows.push(vec![field.label, value]);
+                }
+                _ => sections.push(SheetSection {
+                    title: field.section,
+                    header: row(["", ""]),
+                    rows: vec![vec![field.label, value]],
+                }),
+            }
+        }
+
+        if let Some(compare) = compare {
+            let rows = diff(&compare.setup, setup)
+                .into_iter()
+                .map(|change| {
+                    let clicks = change.clicks().map(|c| format!("{c:+}")).unwrap_or_default();
+                    vec![
+                        change.label.clone(),
+                        change.format(&change.to),
+                        change.format(&change.from),
+                        clicks,
+                    ]
+                })
+                .collect();
+
+            sections.push(SheetSection {
+                title: format!("Compared to {}", compare.name),
+                header: row(["", &file.name, &compare.name, "Clicks"]),
+                rows,
+            });
+        }
+
+        SetupSheet {
+            title: format!("{} - {} - {}", setup.car_name, track, file.name),
+            sections,
+        }
+    }
+
+    /// File name of the sheet, e.g. `bmw_m4_gt3 spa Race.html`
+    pub fn file_name(&self, format: SheetFormat) -> String {
+        format!("{}.{}", self.title.replace(" - ", " "), format.extension())
+    }
+
+    pub fn render(&self, format: SheetFormat) -> String {
+        match format {
+            SheetFormat::Html => self.html(),
+            SheetFormat::Markdown => self.markdown(),
+        }
+    }
+
+    pub fn save(&self, folder: &Path, format: SheetFormat) -> Result<PathBuf, SetupError> {
+        std::fs::create_dir_all(folder)?;
+        let path = folder.join(self.file_name(format));
+        std::fs::write(&path, self.render(format))?;
+
+        Ok(path)
+    }
+
+    fn html(&self) -> String {
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(&format!("<title>{}</title>\n", escape(&self.title)));
+        html.push_str(
+            "<style>\n\
+             body { font-family: sans-serif; font-size: 12px; }\n\
+             table { border-collapse: collapse; margin-bottom: 12px; min-width: 50%; }\n\
+             th, td { border: 1px solid #999; padding: 2px 8px; text-align: left; }\n\
+             h2 { font-size: 14px; margin: 8px 0 4px; }\n\
+             section { break-inside: avoid; }\n\
+             </style>\n</head>\n<body>\n",
+        );
+        html.push_str(&format!("<h1>{}</h1>\n", escape(&self.title)));
+
+        for section in &self.sections {
+            html.push_str(&format!(
+                "<section>\n<h2>{}</h2>\n<table>\n",
+                escape(&section.title)
+            ));
+            if section.header.iter().any(|cell| !cell.is_empty()) {
+                html.push_str("<tr>");
+                for cell in &section.header {
+                    html.push_str(&format!("<th>{}</th>", escape(cell)));
+                }
+                html.push_str("</tr>\n");
+            }
+            for row in &section.rows {
+                html.push_str("<tr>");
+                for cell in row {
+                    html.push_str(&format!("<td>{}</td>", escape(cell)));
+                }
+                html.push_str("</tr>\n");
+            }
+            html.push_str("</table>\n</section>\n");
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    fn markdown(&self) -> String {
+        let mut markdown = format!("# {}\n", self.title);
+
+        for section in &self.sections {
+            markdown.push_str(&format!("\n## {}\n\n", section.title));
+            markdown.push_str(&markdown_row(&section.header));
+            markdown.push_str(&markdown_row(&vec!["---".to_owned(); section.header.len()]));
+            for row in &section.rows {
+                markdown.push_str(&markdown_row(row));
+            }
+        }
+
+        markdown
+    }
+}
+
+/// Value in physical units where known, clicks otherwise
+fn format_field(field: &SetupField) -> String {
+    match (&field.value, &field.parameter) {
+        (Value::Number(n), Some(parameter)) if n.is_i64() && parameter.unit != Default::default() => {
+            parameter.format(n.as_i64().unwrap_or_default() as i32)
+        }
+        (Value::Number(n), _) if n.is_f64() => format!("{:.2}", n.as_f64().unwrap_or_default()),
+        (value, _) => value.to_string(),
+    }
+}
+
+fn row<const N: usize>(cells: [&str; N]) -> Vec<String> {
+    cells.into_iter().map(str::to_owned).collect()
+}
+
+fn markdown_row(cells: &[String]) -> String {
+    let cells: Vec<String> = cells
+        .iter()
+        .map(|cell| cell.replace('|', "\\|").replace('\n', " "))
+        .collect();
+    format!("| {} |\n", cells.join(" | "))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\n', "<br>")
+}
+
+#[test]
+fn test_setup_sheet() {
+    let mut file = SetupFile {
+        name: "Race".to_owned(),
+        path: "bmw_m4_gt3/spa".into(),
+        ambient_temperature: 18,
+        track_temperature: 24,
+        setup_type: Default::default(),
+        condition: Default::default(),
+        notes: "Stable | safe".to_owned(),
+        author: String::new(),
+        template: true,
+        patch: None,
+        setup: super::setup::setup(),
+    };
+    file.setup.car_name = "bmw_m4_gt3".to_owned();
+    let mut compare = file.clone();
+    compare.name = "Quali".to_owned();
+    compare.setup.advanced_setup.aero_balance.rear_wing += 2;
+
+    let sheet = SetupSheet::new(&file, Some(&compare));
+    assert_eq!(sheet.title, "bmw_m4_gt3 - spa - Race");
+    assert_eq!(sheet.file_name(SheetFormat::Html), "bmw_m4_gt3 spa Race.html");
+    assert!(sheet.sections.iter().any(|s| s.title == "Tyres"));
+    assert!(sheet.sections.iter().any(|s| s.title == "Strategy"));
+
+    let comparison = sheet.sections.last().unwrap();
+    assert_eq!(comparison.title, "Compared to Quali");
+    assert_eq!(comparison.rows.len(), 1);
+    assert_eq!(comparison.rows[0][3], "-2");
+
+    // Pressures are shown in psi instead of clicks
+    let markdown = sheet.render(SheetFormat::Markdown);
+    assert!(markdown.contains("| Tyre Pressure FL | "));
+    assert!(markdown.contains(" psi |"));
+    assert!(markdown.contains("Stable \\| safe"));
+
+    let html = sheet.render(SheetFormat::Html);
+    assert!(html.contains("<title>bmw_m4_gt3 - spa - Race</title>"));
+    assert!(html.contains("<td>18°C ambient, 24°C track</td>"));
+}
@@ -0,0 +1,393 @@
+use std::{collections::BTreeMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::actors::ui::Weather;
+
+use super::{CarTrack, PressureBuildup, SetupChange, SetupFile, SetupType};
+
+/// Ambient and track temperatures to write adjusted setups for on top of the live weather,
+/// to have them ready in ACC before the session starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemperatureGrid {
+    pub ambient_from: u8,
+    pub ambient_to: u8,
+    pub track_from: u8,
+    pub track_to: u8,
+    /// Degrees between two temperatures of the grid
+    pub step: u8,
+}
+
+impl Default for TemperatureGrid {
+    fn default() -> Self {
+        TemperatureGrid {
+            ambient_from: 14,
+            ambient_to: 30,
+            track_from: 20,
+            track_to: 30,
+            step: 2,
+        }
+    }
+}
+
+impl TemperatureGrid {
+    /// Every ambient and track temperature pair of the grid
+    pub fn points(&self) -> Vec<(u8, u8)> {
+        let step = self.step.max(1) as usize;
+        (self.ambient_from..=self.ambient_to)
+            .step_by(step)
+            .flat_map(|ambient| {
+                (self.track_from..=self.track_to)
+                    .step_by(step)
+                    .map(move |track| (ambient, track))
+            })
+            .collect()
+    }
+}
+
+/// Latest state of the session that the adjusted setups are derived from.
+///
+/// Adjusted setups only depend on the templates and this state, never on the order the changes
+/// arrived in, so a weather or fuel change that comes in before the track is loaded still applies.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DesiredState {
+    pub car_track: Option<CarTrack>,
+    pub weather: Option<Weather>,
+    pub race_fuel: Option<i32>,
+    pub quali_fuel: Option<i32>,
+    pub telemetry_laps: Option<i32>,
+    pub grid: Option<TemperatureGrid>,
+    /// Pressure corrections applied to the adjusted setups only, per tyre compound
+    pub pressure_offsets: BTreeMap<i32, [i32; 4]>,
+    /// Brake duct changes applied to the adjusted race setups
+    pub brake_ducts: [i32; 2],
+}
+
+impl DesiredState {
+    /// Record a change of the session, returns true when the adjusted setups have to be derived again
+    pub fn apply(&mut self, change: &SetupChange) -> bool {
+        let previous = self.clone();
+
+        match change {
+            SetupChange::Load(car, track) => {
+                self.car_track = Some(CarTrack {
+                    car: car.clone(),
+                    track: track.clone(),
+                });
+                // Corrections only hold for the session they were measured in
+                self.pressure_offsets.clear();
+                self.brake_ducts = [0; 2];
+
+                // Templates are read again on every load
+                return true;
+            }
+            SetupChange::Weather(weather) => self.weather = Some(*weather),
+            SetupChange::RaceFuel(fuel) => self.race_fuel = Some(*fuel),
+            SetupChange::QualiFuel(fuel) => self.quali_fuel = Some(*fuel),
+            SetupChange::TelemetryLaps(laps) => self.telemetry_laps = Some(*laps),
+            SetupChange::TemperatureGrid(grid) => self.grid = *grid,
+            _ => {}
+        }
+
+        *self != previous
+    }
+
+    pub fn add_pressure_offset(&mut self, compound: i32, clicks: &[i32; 4]) {
+        let offsets = self.pressure_offsets.entry(compound).or_default();
+        offsets
+            .iter_mut()
+            .zip(clicks)
+            .for_each(|(offset, clicks)| *offset += clicks);
+    }
+
+    pub fn add_brake_ducts(&mut self, clicks: &[i32; 2]) {
+        self.brake_ducts
+            .iter_mut()
+            .zip(clicks)
+            .for_each(|(duct, clicks)| *duct += clicks);
+    }
+
+    /// Every template adjusted to the state, empty until a track is loaded
+    pub fn adjusted(
+        &self,
+        templates: &BTreeMap<String, SetupFile>,
+        buildup: &PressureBuildup,
+        setup_folder: &Path,
+    ) -> BTreeMap<String, SetupFile> {
+        let Some(car_track) = &self.car_track else {
+            return BTreeMap::new();
+        };
+        let folder = setup_folder.join(&car_track.car).join(&car_track.track);
+
+        templates
+            .iter()
+            .map(|(name, template)| (name.clone(), self.adjust(template, buildup, &folder)))
+            .collect()
+    }
+
+    /// Every template adjusted to each temperature of the grid, empty without a grid
+    pub fn grid(
+        &self,
+        templates: &BTreeMap<String, SetupFile>,
+        buildup: &PressureBuildup,
+        setup_folder: &Path,
+    ) -> Vec<SetupFile> {
+        let Some(grid) = &self.grid else {
+            return Vec::new();
+        };
+
+        grid.points()
+            .into_iter()
+            .flat_map(|(ambient_temp, track_temp)| {
+                let state = DesiredState {
+                    weather: Some(Weather {
+                        ambient_temp,
+                        track_temp,
+                        ..self.weather.unwrap_or_default()
+                    }),
+                    grid: None,
+                    ..self.clone()
+                };
+                state.adjusted(templates, buildup, setup_folder).into_values()
+            })
+            .collect()
+    }
+
+    /// Template adjusted to the state, to be written to the folder
+    pub fn adjust(&self, template: &SetupFile, buildup: &PressureBuildup, folder: &Path) -> SetupFile {
+        let mut setup = template.clone();
+        setup.path = folder.to_owned();
+        setup.template = false;
+        setup.patch = None;
+
+        if let Some(weather) = &self.weather {
+            setup.adjust_weather(weather);
+        }
+        self.pressure_offsets
+            .iter()
+            .for_each(|(compound, clicks)| setup.adjust_pressure_clicks(*compound, clicks));
+        if let Some(weather) = &self.weather {
+            let pressure = &setup.parameters().tyre_pressure;
+            buildup.compounds().for_each(|compound| {
+                let clicks = buildup.clicks(compound, setup.setup_type, weather.track_temp as f32, pressure);
+                setup.adjust_pressure_clicks(compound, &clicks)
+            });
+        }
+
+        // Base setups are used for both sessions, the race being the one that needs the fuel
+        let fuel = match setup.setup_type {
+            SetupType::Race => self.race_fuel,
+            SetupType::Qualifying => self.quali_fuel,
+            SetupType::Base => self.race_fuel.or(self.quali_fuel),
+        };
+        if let Some(fuel) = fuel {
+            setup.adjust_fuel(fuel);
+        }
+        if let Some(laps) = self.telemetry_laps {
+            setup.adjust_telemetry_laps(laps);
+        }
+        if setup.setup_type == SetupType::Race && self.brake_ducts != [0; 2] {
+            setup.adjust_brake_ducts(&self.brake_ducts);
+        }
+
+        setup
+    }
+}
+
+#[cfg(test)]
+fn template(name: &str, setup_type: SetupType) -> SetupFile {
+    SetupFile {
+        name: name.to_owned(),
+        path: "SetupTemplates".into(),
+        ambient_temperature: 20,
+        track_temperature: 26,
+        setup_type,
+        condition: Default::default(),
+        notes: String::new(),
+        author: String::new(),
+        template: true,
+        patch: None,
+        setup: super::setup::setup(),
+    }
+}
+
+#[cfg(test)]
+fn permutations(changes: &[SetupChange]) -> Vec<Vec<SetupChange>> {
+    if changes.len() <= 1 {
+        return vec![changes.to_vec()];
+    }
+
+    (0..changes.len())
+        .flat_map(|i| {
+            let mut rest = changes.to_vec();
+            let first = rest.remove(i);
+            permutations(&rest).into_iter().map(move |mut order| {
+                order.insert(0, first.clone());
+                order
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn test_adjusted() {
+    let templates = BTreeMap::from([
+        ("Base".to_owned(), template("Base", SetupType::Base)),
+        ("Race".to_owned(), template("Race", SetupType::Race)),
+        ("Quali".to_owned(), template("Quali", SetupType::Qualifying)),
+    ]);
+    let mut state = DesiredState::default();
+    let buildup = PressureBuildup::default();
+    assert!(state
+        .adjusted(&templates, &buildup, Path::new("Setups"))
+        .is_empty());
+
+    state.apply(&SetupChange::Load("car".to_owned(), "track".to_owned()));
+    state.apply(&SetupChange::Weather(Weather {
+        ambient_temp: 22,
+        track_temp: 30,
+        ..Default::default()
+    }));
+    state.apply(&SetupChange::QualiFuel(20));
+    state.apply(&SetupChange::TelemetryLaps(10));
+    state.add_brake_ducts(&[1, 1]);
+
+    let adjusted = state.adjusted(&templates, &buildup, Path::new("Setups"));
+    let template = &templates["Race"].setup;
+
+    let race = &adjusted["Race"];
+    assert_eq!(race.path, Path::new("Setups").join("car").join("track"));
+    assert!(!race.template);
+    assert_eq!(race.ambient_temperature, 22);
+    assert_eq!(
+        race.setup.basic_setup.tyres.tyre_pressure,
+        template.basic_setup.tyres.tyre_pressure.map(|p| p - 2)
+    );
+    assert_eq!(
+        race.setup.basic_setup.strategy.fuel,
+        template.basic_setup.strategy.fuel
+    );
+    assert_eq!(race.setup.basic_setup.electronics.telemetry_laps, 10);
+    assert_eq!(
+        race.setup.advanced_setup.aero_balance.brake_duct,
+        template.advanced_setup.aero_balance.brake_duct.map(|d| d + 1)
+    );
+
+    // Without race fuel base setups use the qualifying fuel
+    assert_eq!(adjusted["Quali"].setup.basic_setup.strategy.fuel, 18);
+    assert_eq!(adjusted["Base"].setup.basic_setup.strategy.fuel, 18);
+    assert_eq!(
+        adjusted["Base"].setup.advanced_setup.aero_balance.brake_duct,
+        template.advanced_setup.aero_balance.brake_duct
+    );
+
+    state.apply(&SetupChange::RaceFuel(60));
+    let adjusted = state.adjusted(&templates, &buildup, Path::new("Setups"));
+    assert_eq!(adjusted["Race"].setup.basic_setup.strategy.fuel, 58);
+    assert_eq!(adjusted["Base"].setup.basic_setup.strategy.fuel, 58);
+    assert_eq!(adjusted["Quali"].setup.basic_setup.strategy.fuel, 18);
+}
+
+#[test]
+fn test_message_ordering() {
+    let templates = BTreeMap::from([
+        ("Base".to_owned(), template("Base", SetupType::Base)),
+        ("Race".to_owned(), template("Race", SetupType::Race)),
+        ("Quali".to_owned(), template("Quali", SetupType::Qualifying)),
+    ]);
+    let buildup = PressureBuildup::default();
+    let changes = [
+        SetupChange::Load("car".to_owned(), "track".to_owned()),
+        SetupChange::Weather(Weather {
+            ambient_temp: 17,
+            track_temp: 21,
+            ..Default::default()
+        }),
+        SetupChange::RaceFuel(80),
+        SetupChange::QualiFuel(15),
+        SetupChange::TelemetryLaps(5),
+    ];
+
+    let mut expected = DesiredState::default();
+    for change in &changes {
+        expected.apply(change);
+    }
+    let expected = expected.adjusted(&templates, &buildup, Path::new("Setups"));
+
+    let orders = permutations(&changes);
+    assert_eq!(orders.len(), 120);
+    for order in orders {
+        let mut state = DesiredState::default();
+        for change in &order {
+            state.apply(change);
+        }
+
+        let adjusted = state.adjusted(&templates, &buildup, Path::new("Setups"));
+        assert_eq!(adjusted.len(), expected.len());
+        for (name, setup) in &adjusted {
+            assert_eq!(setup.setup, expected[name].setup, "{name} after {order:?}");
+            assert_eq!(setup.path_with_name(), expected[name].path_with_name());
+        }
+    }
+
+    // A later load keeps the session values but drops the corrections of the previous track
+    let mut state = DesiredState::default();
+    for change in &changes {
+        state.apply(change);
+    }
+    state.add_pressure_offset(0, &[1, 1, 1, 1]);
+    assert!(state.apply(&SetupChange::Load("car".to_owned(), "other".to_owned())));
+    assert!(state.pressure_offsets.is_empty());
+    assert_eq!(state.race_fuel, Some(80));
+    assert!(!state.apply(&SetupChange::RaceFuel(80)));
+}
+
+#[test]
+fn test_grid() {
+    let templates = BTreeMap::from([
+        ("Race".to_owned(), template("Race", SetupType::Race)),
+        ("Quali".to_owned(), template("Quali", SetupType::Qualifying)),
+    ]);
+    let buildup = PressureBuildup::default();
+    let mut state = DesiredState::default();
+    state.apply(&SetupChange::Load("car".to_owned(), "track".to_owned()));
+    assert!(state.grid(&templates, &buildup, Path::new("Setups")).is_empty());
+
+    let grid = TemperatureGrid {
+        ambient_from: 16,
+        ambient_to: 21,
+        track_from: 24,
+        track_to: 26,
+        step: 2,
+    };
+    assert_eq!(
+        grid.points(),
+        [(16, 24), (16, 26), (18, 24), (18, 26), (20, 24), (20, 26)]
+    );
+
+    assert!(state.apply(&SetupChange::TemperatureGrid(Some(grid))));
+    state.apply(&SetupChange::RaceFuel(60));
+    let setups = state.grid(&templates, &buildup, Path::new("Setups"));
+    assert_eq!(setups.len(), 12);
+
+    // Every point is adjusted like the live weather would be
+    let mut live = DesiredState {
+        grid: None,
+        ..state.clone()
+    };
+    live.apply(&SetupChange::Weather(Weather {
+        ambient_temp: 18,
+        track_temp: 26,
+        ..Default::default()
+    }));
+    let expected = &live.adjusted(&templates, &buildup, Path::new("Setups"))["Race"];
+    let race = setups
+        .iter()
+        .find(|setup| setup.path_with_name() == expected.path_with_name())
+        .unwrap();
+    assert_eq!(race.setup, expected.setup);
+    assert_eq!(race.setup.basic_setup.strategy.fuel, 58);
+
+    assert!(state.apply(&SetupChange::TemperatureGrid(None)));
+    assert!(state.grid(&templates, &buildup, Path::new("Setups")).is_empty());
+}
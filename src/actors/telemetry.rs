@@ -1,21 +1,24 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use actix::prelude::*;
 use tracing::{debug, error};
 
 use crate::{
-    actors::ui::UiUpdate,
+    actors::ui::{TrackConditions, UiUpdate},
     telemetry::{
         self,
+        broadcast::CarLocation,
+        pit::{PitDetector, PitEvent, PitSample},
         shm::{
             self, Graphics, LapHistory, PageFileGraphics, PageFilePhysics, PageFileStatic, Physics,
             SharedMemoryPage, StaticData,
         },
+        stints::Stint,
         TelemetryError,
     },
 };
 
-use super::{fuel_calculator::FuelMessage, setup_manager::SetupChange, Router};
+use super::{fuel_calculator::FuelMessage, setup_manager::SetupChange, Router, SessionEvent};
 
 pub struct Telemetry {
     interval: SpawnHandle,
@@ -28,6 +31,17 @@ pub struct Telemetry {
 
     pub lap_history: LapHistory,
     pub lap_result: telemetry::LapWheels,
+
+    /// Laps completed since leaving the pit lane
+    stint_lap: i32,
+    /// A session was started and not ended yet
+    in_session: bool,
+
+    /// Location of the car from the broadcast api
+    car_location: CarLocation,
+    pit_detector: PitDetector,
+    /// Clock the pit stops are timed with
+    clock: Instant,
 }
 
 impl Telemetry {
@@ -41,6 +55,11 @@ impl Telemetry {
             graphics: Default::default(),
             lap_history: Default::default(),
             lap_result: Default::default(),
+            stint_lap: 0,
+            in_session: false,
+            car_location: Default::default(),
+            pit_detector: Default::default(),
+            clock: Instant::now(),
         }
     }
 
@@ -104,13 +123,115 @@ impl Telemetry {
         }
     }
 
+    /// Forward changes of the track surface and rain state
+    fn track_conditions(&mut self, update: &TelemetryUpdate) {
+        if update.graphics.track_grip_status != self.graphics.track_grip_status
+            || update.graphics.rain_intensity != self.graphics.rain_intensity
+        {
+            let conditions = TrackConditions {
+                grip_status: update.graphics.track_grip_status,
+                rain_intensity: update.graphics.rain_intensity,
+            };
+
+            self.router.do_send(UiUpdate::TrackConditions(conditions));
+            self.router.do_send(SetupChange::TrackConditions(conditions));
+        }
+    }
+
+    /// Forward the start and end of sessions, a new session starts whenever the session type
+    /// or index changes, e.g. going from qualifying to the race
+    fn session(&mut self, update: &TelemetryUpdate) {
+        let live = matches!(update.graphics.status, shm::Status::Live | shm::Status::Pause);
+        let changed = update.graphics.session != self.graphics.session
+            || update.graphics.session_index != self.graphics.session_index;
+
+        if self.in_session && (!live || changed) {
+            self.end_session();
+        }
+        if live && !self.in_session {
+            self.in_session = true;
+            self.router.do_send(SessionEvent::Started(
+                update.static_data.car_model.clone(),
+                update.static_data.track.clone(),
+                update.graphics.session,
+            ));
+
+            self.pit_detector = PitDetector::default();
+            self.start_stint(Stint {
+                first_lap: update.graphics.completed_laps + 1,
+                tyre_set: update.graphics.current_tyre_set,
+                pit_stop: None,
+            });
+        }
+    }
+
+    /// Detect pit stops of the player's car, leaving the pit lane starts a new stint
+    fn pit_lane(&mut self, update: &TelemetryUpdate) {
+        if !self.in_session {
+            return;
+        }
+
+        let sample = PitSample {
+            time: self.clock.elapsed(),
+            in_pit_lane: update.graphics.is_in_pit_lane,
+            in_pit: update.graphics.is_in_pit,
+            location: self.car_location,
+            speed_kmh: update.physics.speed_kmh,
+            fuel: update.physics.fuel,
+            tyre_set: update.graphics.current_tyre_set,
+        };
+        match self.pit_detector.update(&sample) {
+            Some(PitEvent::Entered) => debug!("entered the pit lane"),
+            Some(PitEvent::Exited(pit_stop)) => {
+                debug!("left the pit lane: {pit_stop:?}");
+                self.start_stint(Stint {
+                    first_lap: update.graphics.completed_laps + 1,
+                    tyre_set: update.graphics.current_tyre_set,
+                    pit_stop: Some(pit_stop),
+                });
+            }
+            None => {}
+        }
+    }
+
+    fn start_stint(&mut self, stint: Stint) {
+        self.stint_lap = 0;
+        self.router.do_send(SessionEvent::Stint(stint.clone()));
+        self.router.do_send(UiUpdate::Stint(stint));
+    }
+
+    fn end_session(&mut self) {
+        if self.in_session {
+            self.in_session = false;
+            self.router.do_send(SessionEvent::Ended);
+        }
+    }
+
     /// Compute lap results and reset history struct
     fn lap_history(&mut self) {
         self.lap_result.get_avg_min_max(&self.lap_history);
         self.lap_result.number = self.graphics.completed_laps + 1;
+        self.lap_result.rain_tyres = self.graphics.rain_tyres;
+        self.lap_result.ambient_temperature = self.physics.air_temperature;
+        self.lap_result.track_temperature = self.physics.road_temperature;
+        self.lap_result.brake_pads = [
+            self.physics.front_brake_compound,
+            self.physics.rear_brake_compound,
+        ];
 
-        self.router
-            .do_send(UiUpdate::LapWheels(std::mem::take(&mut self.lap_result)));
+        // Laps ending in the pit lane are in laps and end the stint, leaving the pit lane starts
+        // a new stint whose out lap is its first lap
+        self.stint_lap = if self.pit_detector.in_pit_lane() {
+            0
+        } else {
+            self.stint_lap + 1
+        };
+        self.lap_result.stint_lap = self.stint_lap;
+
+        let lap = std::mem::take(&mut self.lap_result);
+        self.router.do_send(SessionEvent::Wheels(lap.clone()));
+        self.router.do_send(SetupChange::LapWheels(lap.clone()));
+        self.router.do_send(UiUpdate::LapWheels(lap));
 
         self.lap_history = LapHistory::default();
     }
@@ -122,6 +243,8 @@ impl Telemetry {
                 if l_graphics.fuel_used_per_lap != update.graphics.fuel_used_per_lap {
                     self.router
                         .do_send(FuelMessage::FuelPerLap(self.graphics.fuel_used_per_lap));
+                    self.router
+                        .do_send(SessionEvent::FuelPerLap(self.graphics.fuel_used_per_lap));
                 }
 
                 // Compute lap results and reset history structs
@@ -153,6 +276,9 @@ impl Actor for Telemetry {
                 |telemetry, ctx| match Telemetry::get_update() {
                     Ok(update) => {
                         telemetry.game_state(&update, ctx);
+                        telemetry.track_conditions(&update);
+                        telemetry.session(&update);
+                        telemetry.pit_lane(&update);
                         telemetry.update(update);
                     }
                     Err(error) => {
@@ -165,6 +291,7 @@ impl Actor for Telemetry {
                             error!("could not connect to telemetry: {:?}", error);
                             telemetry.router.do_send(super::ShmGameState::Disconnected);
                         };
+                        telemetry.end_session();
                     }
                 },
             );
@@ -180,6 +307,19 @@ impl Actor for Telemetry {
 #[rtype(result = "i16")]
 pub struct CarID;
 
+/// Location of the player's car reported by the broadcast api
+#[derive(Debug, Clone, Copy, Message)]
+#[rtype(result = "()")]
+pub struct PlayerLocation(pub CarLocation);
+
+impl Handler<PlayerLocation> for Telemetry {
+    type Result = ();
+
+    fn handle(&mut self, msg: PlayerLocation, _ctx: &mut Self::Context) -> Self::Result {
+        self.car_location = msg.0;
+    }
+}
+
 impl Handler<CarID> for Telemetry {
     type Result = i16;
 
@@ -1,16 +1,37 @@
 use std::{
     collections::{BTreeMap, HashMap},
+    path::PathBuf,
     time::Duration,
 };
 
 use actix::prelude::*;
+#[cfg(feature = "app")]
 use dioxus::signals::{SyncSignal, Writable};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "app")]
 use tracing::debug;
 
-use crate::telemetry::{broadcast::LapType, LapTime, LapWheels};
+use crate::telemetry::{
+    broadcast::LapType,
+    shm::{RainIntensity, TrackGripStatus},
+    stints::Stint,
+    LapTime, LapWheels,
+};
 
-use super::{fuel_calculator::FuelData, setup_manager::SetupFile, Reset, Router};
+use super::{
+    fuel_calculator::FuelData,
+    motec::MotecSession,
+    pit_loss::PitLoss,
+    sessions::{Session, SessionSummary},
+    setup_manager::{
+        BatchPreview, BrakeDuctRecommendation, BundlePreview, PersonalBest, PressureCorrection,
+        SetupCondition, SetupFile, SetupLibrary, SetupVersion,
+    },
+};
+#[cfg(feature = "app")]
+use super::{Reset, Router};
 
+#[cfg(feature = "app")]
 #[derive(Debug, Clone, Message)]
 #[rtype(result = "()")]
 pub struct UiState {
@@ -20,12 +41,15 @@ pub struct UiState {
     laps: SyncSignal<Laps>,
     setups: SyncSignal<Setups>,
     fuel_data: SyncSignal<FuelData>,
+    history: SyncSignal<SessionHistory>,
 }
 
+#[cfg(feature = "app")]
 impl Actor for UiState {
     type Context = Context<Self>;
 }
 
+#[cfg(feature = "app")]
 impl UiState {
     pub fn initialize(
         router: Addr<Router>,
@@ -33,6 +57,7 @@ impl UiState {
         laps: SyncSignal<Laps>,
         setups: SyncSignal<Setups>,
         fuel_data: SyncSignal<FuelData>,
+        history: SyncSignal<SessionHistory>,
     ) -> Addr<UiState> {
         let arb = Arbiter::new();
         let ui = UiState {
@@ -41,6 +66,7 @@ impl UiState {
             laps,
             setups,
             fuel_data,
+            history,
         };
         UiState::start_in_arbiter(&arb.handle(), |_| ui)
     }
@@ -52,10 +78,17 @@ pub struct SessionInfo {
     pub name: String,
     pub time: Duration,
     pub weather: Weather,
+    pub conditions: TrackConditions,
     pub live: bool,
+    /// Last session whose MoTeC files were archived
+    pub motec: Option<MotecSession>,
+    /// Personal best of the car on the track
+    pub personal_best: PersonalBest,
+    /// Time spent and lost in the pits on the track
+    pub pit_loss: Option<PitLoss>,
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Weather {
     pub ambient_temp: u8,
     pub track_temp: u8,
@@ -64,20 +97,43 @@ pub struct Weather {
     pub wetness: u8,
 }
 
+/// Track surface and rain state as reported by the shared memory
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TrackConditions {
+    pub grip_status: TrackGripStatus,
+    pub rain_intensity: RainIntensity,
+}
+
 #[derive(Debug, Clone, Message)]
 #[rtype(result = "()")]
 pub enum UiUpdate {
     TrackName(String),
     Weather(Weather),
+    TrackConditions(TrackConditions),
     SessionTime(Duration),
     SessionLive(bool),
     LapTime(LapTimeData),
     LapWheels(LapWheels),
+    Stint(Stint),
     SetupTemplates(BTreeMap<String, SetupFile>),
     SetupAdjusted(BTreeMap<String, SetupFile>),
+    SetupCondition(SetupCondition),
+    SetupHistory(BTreeMap<String, Vec<SetupVersion>>),
+    SetupLibrary(SetupLibrary),
+    SetupBundle(Option<BundlePreview>),
+    BatchPreview(Option<BatchPreview>),
+    SetupSheet(PathBuf),
+    MotecSession(MotecSession),
+    PersonalBest(PersonalBest),
+    PitLoss(Option<PitLoss>),
+    Sessions(Vec<SessionSummary>),
+    Session(Option<Session>),
+    PressureCorrection(Option<PressureCorrection>),
+    BrakeDucts(Option<BrakeDuctRecommendation>),
     FuelData(FuelData),
 }
 
+#[cfg(feature = "app")]
 impl Handler<UiUpdate> for UiState {
     type Result = ();
 
@@ -86,36 +142,56 @@ impl Handler<UiUpdate> for UiState {
         match msg {
             UiUpdate::TrackName(name) => self.session_info.write().name = name,
             UiUpdate::Weather(weather) => self.session_info.write().weather = weather,
+            UiUpdate::TrackConditions(conditions) => self.session_info.write().conditions = conditions,
             UiUpdate::SessionTime(time) => self.session_info.write().time = time,
             UiUpdate::SessionLive(live) => self.session_info.write().live = live,
             UiUpdate::LapTime(time) => self.laps.write().insert_time(time),
             UiUpdate::LapWheels(wheels) => self.laps.write().insert_wheels(wheels),
+            UiUpdate::Stint(stint) => self.laps.write().stints.push(stint),
             UiUpdate::SetupTemplates(setups) => self.setups.write().templates = setups,
             UiUpdate::SetupAdjusted(setups) => self.setups.write().adjusted = setups,
+            UiUpdate::SetupCondition(condition) => self.setups.write().condition = condition,
+            UiUpdate::PressureCorrection(correction) => self.setups.write().pressure_correction = correction,
+            UiUpdate::BrakeDucts(recommendation) => self.setups.write().brake_ducts = recommendation,
+            UiUpdate::SetupHistory(history) => self.setups.write().history = history,
+            UiUpdate::SetupLibrary(library) => self.setups.write().library = library,
+            UiUpdate::SetupBundle(bundle) => self.setups.write().bundle = bundle,
+            UiUpdate::BatchPreview(batch) => self.setups.write().batch = batch,
+            UiUpdate::SetupSheet(path) => self.setups.write().sheet = Some(path),
+            UiUpdate::MotecSession(session) => self.session_info.write().motec = Some(session),
+            UiUpdate::PersonalBest(best) => self.session_info.write().personal_best = best,
+            UiUpdate::PitLoss(pit_loss) => self.session_info.write().pit_loss = pit_loss,
+            UiUpdate::Sessions(sessions) => self.history.write().sessions = sessions,
+            UiUpdate::Session(session) => self.history.write().session = session,
             UiUpdate::FuelData(fuel) => self.fuel_data.write().replace(fuel),
         }
     }
 }
 
-#[derive(Debug, Default, Clone, Message)]
+#[derive(Debug, Default, Clone, PartialEq, Message)]
 #[rtype(result = "()")]
 pub struct Laps {
     pub times: HashMap<i32, LapTimeData>,
     pub wheels: HashMap<i32, LapWheels>,
+    /// Stints detected from the pit lane, oldest first
+    pub stints: Vec<Stint>,
 }
 
 impl Laps {
     pub fn reset(&mut self) {
         self.times.clear();
         self.wheels.clear();
+        self.stints.clear();
     }
 }
 
 impl Laps {
+    #[cfg(feature = "app")]
     fn insert_time(&mut self, time: LapTimeData) {
         let _ = self.times.insert(time.number, time);
     }
 
+    #[cfg(feature = "app")]
     fn insert_wheels(&mut self, wheels: LapWheels) {
         let _ = self.wheels.insert(wheels.number, wheels);
     }
@@ -170,7 +246,7 @@ impl<'a> Iterator for LapsIter<'a> {
     }
 }
 
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LapTimeData {
     pub number: i32,
     pub sectors: Vec<LapTime>,
@@ -184,8 +260,29 @@ pub struct LapTimeData {
 pub struct Setups {
     pub templates: BTreeMap<String, SetupFile>,
     pub adjusted: BTreeMap<String, SetupFile>,
+    pub condition: SetupCondition,
+    pub pressure_correction: Option<PressureCorrection>,
+    pub brake_ducts: Option<BrakeDuctRecommendation>,
+    pub history: BTreeMap<String, Vec<SetupVersion>>,
+    pub library: SetupLibrary,
+    /// Bundle selected for import with its conflicts
+    pub bundle: Option<BundlePreview>,
+    /// Changes of the batch edit waiting to be applied
+    pub batch: Option<BatchPreview>,
+    /// Last exported setup sheet
+    pub sheet: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Clone, Message)]
+#[rtype(result = "()")]
+pub struct SessionHistory {
+    /// Stored sessions, newest first
+    pub sessions: Vec<SessionSummary>,
+    /// Session opened in the history
+    pub session: Option<Session>,
 }
 
+#[cfg(feature = "app")]
 impl Handler<Reset> for UiState {
     type Result = ();
 
@@ -0,0 +1,213 @@
+#![warn(clippy::all, rust_2018_idioms)]
+
+//! Adjust a folder of setup templates without the game or the app,
+//! e.g. to prepare the setups of a league race in a script
+
+use std::{path::PathBuf, process::ExitCode, str::FromStr, time::Duration};
+
+use thiserror::Error;
+use vapor_manager::{
+    actors::{
+        fuel_calculator::FuelData,
+        setup_manager::{read_templates, DesiredState, SetupError, SetupMeta},
+        ui::Weather,
+    },
+    telemetry::LapTime,
+};
+
+static USAGE: &str = "\
+usage: vapor-cli --templates <DIR> --output <DIR> --ambient <°C> --track <°C> [OPTIONS]
+
+Adjusts every template in a folder to the temperatures and writes the results to the output folder.
+
+options:
+    --race-minutes <MIN>     length of the race to compute the race fuel for
+    --quali-minutes <MIN>    length of the qualifying to compute the qualifying fuel for
+    --lap-time <M:SS.mmm>    average lap time, defaults to the one stored with the templates
+    --fuel-per-lap <L>       fuel consumption, defaults to the one of the first template
+    --reserve-laps <LAPS>    laps of fuel added on top of the race length
+    --telemetry-laps <LAPS>  telemetry laps of the setups";
+
+#[derive(Debug, Error)]
+enum CliError {
+    #[error("{0}\n\n{USAGE}")]
+    Usage(String),
+    #[error("{USAGE}")]
+    Help,
+    #[error("{0}")]
+    Setup(#[from] SetupError),
+}
+
+/// Command line options, see [USAGE]
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Options {
+    templates: PathBuf,
+    output: PathBuf,
+    ambient: u8,
+    track: u8,
+    race_minutes: Option<u64>,
+    quali_minutes: Option<u64>,
+    lap_time: Option<LapTime>,
+    fuel_per_lap: Option<f32>,
+    reserve_laps: i32,
+    telemetry_laps: Option<i32>,
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, CliError> {
+        let mut options = Options::default();
+        let (mut templates, mut output, mut ambient, mut track) = (None, None, None, None);
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--help" || arg == "-h" {
+                return Err(CliError::Help);
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| CliError::Usage(format!("missing value for {arg}")))?;
+
+            match arg.as_str() {
+                "--templates" => templates = Some(PathBuf::from(value)),
+                "--output" => output = Some(PathBuf::from(value)),
+                "--ambient" => ambient = Some(parse(&arg, &value)?),
+                "--track" => track = Some(parse(&arg, &value)?),
+                "--race-minutes" => options.race_minutes = Some(parse(&arg, &value)?),
+                "--quali-minutes" => options.quali_minutes = Some(parse(&arg, &value)?),
+                "--lap-time" => options.lap_time = Some(parse_lap_time(&value)?),
+                "--fuel-per-lap" => options.fuel_per_lap = Some(parse(&arg, &value)?),
+                "--reserve-laps" => options.reserve_laps = parse(&arg, &value)?,
+                "--telemetry-laps" => options.telemetry_laps = Some(parse(&arg, &value)?),
+                _ => return Err(CliError::Usage(format!("unknown option {arg}"))),
+            }
+        }
+
+        options.templates = required(templates, "--templates")?;
+        options.output = required(output, "--output")?;
+        options.ambient = required(ambient, "--ambient")?;
+        options.track = required(track, "--track")?;
+
+        Ok(options)
+    }
+}
+
+fn required<T>(value: Option<T>, arg: &str) -> Result<T, CliError> {
+    value.ok_or_else(|| CliError::Usage(format!("{arg} is required")))
+}
+
+fn parse<T: FromStr>(arg: &str, value: &str) -> Result<T, CliError> {
+    value
+        .parse()
+        .map_err(|_| CliError::Usage(format!("invalid value {value} for {arg}")))
+}
+
+/// Lap time as `M:SS.mmm` or in seconds
+fn parse_lap_time(value: &str) -> Result<LapTime, CliError> {
+    let (minutes, seconds) = value.split_once(':').unwrap_or(("0", value));
+    let minutes: u64 = parse("--lap-time", minutes)?;
+    let seconds: f64 = parse("--lap-time", seconds)?;
+
+    Ok(Duration::from_secs_f64(minutes as f64 * 60.0 + seconds).into())
+}
+
+/// Adjust the templates and write them to the output folder, returns the written files
+fn run(options: &Options) -> Result<Vec<PathBuf>, CliError> {
+    let templates = read_templates(&options.templates)?;
+    let meta = SetupMeta::read(&options.templates);
+
+    let fuel_per_lap = options.fuel_per_lap.or_else(|| {
+        templates
+            .values()
+            .next()
+            .map(|template| template.setup.basic_setup.strategy.fuel_per_lap)
+    });
+    let mut fuel = FuelData {
+        race_length: Duration::from_secs(options.race_minutes.unwrap_or_default() * 60),
+        quali_length: Duration::from_secs(options.quali_minutes.unwrap_or_default() * 60),
+        avg_lap_time: options.lap_time.clone().unwrap_or(meta.avg_lap),
+        fuel_per_lap: fuel_per_lap.unwrap_or_default(),
+        reserve_laps: options.reserve_laps,
+        ..Default::default()
+    };
+
+    let state = DesiredState {
+        weather: Some(Weather {
+            ambient_temp: options.ambient,
+            track_temp: options.track,
+            ..Default::default()
+        }),
+        race_fuel: fuel.calculate_race(),
+        quali_fuel: fuel.calculate_quali(),
+        telemetry_laps: options.telemetry_laps,
+        ..Default::default()
+    };
+    if let Some(race_fuel) = state.race_fuel {
+        println!("race fuel: {race_fuel} l");
+    }
+    if let Some(quali_fuel) = state.quali_fuel {
+        println!("qualifying fuel: {quali_fuel} l");
+    }
+
+    std::fs::create_dir_all(&options.output).map_err(SetupError::from)?;
+    templates
+        .values()
+        .map(|template| {
+            let setup = state.adjust(template, &meta.pressure_buildup, &options.output);
+            let path = setup.path_with_name();
+            std::fs::write(&path, setup.contents()?)?;
+
+            Ok(path)
+        })
+        .collect::<Result<_, SetupError>>()
+        .map_err(CliError::from)
+}
+
+fn main() -> ExitCode {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::WARN)
+        .with_writer(std::io::stderr)
+        .init();
+
+    let result = Options::parse(std::env::args().skip(1)).and_then(|options| run(&options));
+    match result {
+        Ok(paths) => {
+            paths.iter().for_each(|path| println!("{}", path.display()));
+            ExitCode::SUCCESS
+        }
+        Err(CliError::Help) => {
+            println!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[test]
+fn test_options() {
+    let args = |args: &str| args.split(' ').map(str::to_owned).collect::<Vec<_>>();
+
+    let options = Options::parse(args(
+        "--templates in --output out --ambient 18 --track 24 --race-minutes 60 --lap-time 1:48.500",
+    ))
+    .unwrap();
+    assert_eq!(options.templates, PathBuf::from("in"));
+    assert_eq!((options.ambient, options.track), (18, 24));
+    assert_eq!(options.race_minutes, Some(60));
+    assert_eq!(
+        options.lap_time.unwrap().duration(),
+        Duration::from_millis(108_500)
+    );
+    assert_eq!(options.fuel_per_lap, None);
+
+    assert!(matches!(
+        Options::parse(args("--templates in --output out --ambient 18")),
+        Err(CliError::Usage(_))
+    ));
+    assert!(matches!(
+        Options::parse(args("--templates in --output out --ambient hot --track 24")),
+        Err(CliError::Usage(_))
+    ));
+}
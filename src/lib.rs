@@ -0,0 +1,100 @@
+#![warn(clippy::all, rust_2018_idioms)]
+#![allow(non_snake_case)]
+
+use std::path::PathBuf;
+
+use actors::ui::Weather;
+use telemetry::{broadcast::LapType, shm::SessionType, LapTime, LapWheels};
+
+pub mod actors;
+// pub mod setup;
+pub mod telemetry;
+
+// mod widgets;
+// pub mod components;
+
+#[cfg(feature = "app")]
+pub mod ui;
+
+pub static PROGRAM_NAME: &'static str = "Vapor Manager";
+
+/// The users documents folder, a local `./setups` folder when not running on windows
+pub fn documents_folder() -> PathBuf {
+    #[cfg(windows)]
+    let documents = known_folders::get_known_folder_path(known_folders::KnownFolder::Documents).unwrap();
+    #[cfg(not(windows))]
+    let documents = PathBuf::from("./setups");
+
+    documents
+}
+
+/// Folder the program keeps its own data in
+pub fn data_folder() -> PathBuf {
+    documents_folder().join(PROGRAM_NAME)
+}
+
+/// Empty temporary folder of a single test, removed again when it is dropped
+#[cfg(test)]
+pub(crate) struct TestFolder(PathBuf);
+
+#[cfg(test)]
+impl TestFolder {
+    /// Folder named after the test, unique per process and call so tests can run in parallel
+    pub fn new(name: &str) -> Self {
+        static COUNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let count = COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let folder = std::env::temp_dir().join(format!(
+            "vapor-manager-test-{name}-{}-{count}",
+            std::process::id()
+        ));
+
+        let _ = std::fs::remove_dir_all(&folder);
+        std::fs::create_dir_all(&folder).expect("failed to create test folder");
+        TestFolder(folder)
+    }
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TestFolder {
+    type Target = std::path::Path;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl AsRef<std::path::Path> for TestFolder {
+    fn as_ref(&self) -> &std::path::Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestFolder {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, actix::Message)]
+#[rtype(result = "()")]
+pub enum StateChange {
+    Weather(Weather),
+    TrackName(String),
+    SessionType(SessionType),
+    ShmConnected(bool),
+    BroadcastConnected(bool),
+    LapWheels(LapWheels),
+    LapTimeData(LapTimeData),
+    Reset,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LapTimeData {
+    pub number: i32,
+    pub sectors: Vec<LapTime>,
+    pub time: LapTime,
+    pub valid: bool,
+    pub lap_type: LapType,
+}
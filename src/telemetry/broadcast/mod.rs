@@ -11,9 +11,9 @@ use num_enum::FromPrimitive;
 mod framed;
 pub use framed::{BroadcastCodec, FramedError};
 
-#[cfg(debug_assertions)]
+#[cfg(all(debug_assertions, feature = "app"))]
 mod debugger;
-#[cfg(debug_assertions)]
+#[cfg(all(debug_assertions, feature = "app"))]
 pub use debugger::*;
 
 mod broadcasting_event;
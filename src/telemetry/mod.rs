@@ -5,7 +5,9 @@ use thiserror::Error;
 use tracing::error;
 
 pub mod broadcast;
+pub mod pit;
 pub mod shm;
+pub mod stints;
 
 use shm::{AvgMinMax, Wheels};
 
@@ -34,12 +36,24 @@ impl LapTime {
     }
 }
 
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LapWheels {
     pub number: i32,
+    /// Lap was driven on rain tyres
+    pub rain_tyres: bool,
+    /// Number of the lap within the stint, 0 for in laps
+    pub stint_lap: i32,
+    /// Air temperature at the end of the lap
+    pub ambient_temperature: f32,
+    /// Road temperature at the end of the lap
+    pub track_temperature: f32,
     pub tyre_pressure: AvgMinMax<Wheels<f32>>,
     pub tyre_temperature: AvgMinMax<Wheels<f32>>,
     pub brake_temperature: AvgMinMax<Wheels<f32>>,
+    /// Brake pad compounds in front, rear order
+    pub brake_pads: [i32; 2],
+    /// Brake pad life used up during the lap
+    pub pad_wear: Wheels<f32>,
 }
 
 #[derive(Error, Debug)]
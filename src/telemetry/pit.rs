@@ -0,0 +1,204 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::telemetry::broadcast::CarLocation;
+
+/// Speed below which the car counts as stationary in its pit box
+const STATIONARY_SPEED: f32 = 1.0;
+/// Speed the car enters the pit lane with at least, below it the car was teleported to the garage
+const ENTRY_SPEED: f32 = 10.0;
+
+/// State of the player's car at one telemetry update
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PitSample {
+    /// Time of the update since any fixed point
+    pub time: Duration,
+    pub in_pit_lane: bool,
+    /// Car is in its pit box
+    pub in_pit: bool,
+    /// Location reported by the broadcast api, it lags behind the shared memory and is only used
+    /// until the shared memory reports the pit lane
+    pub location: CarLocation,
+    pub speed_kmh: f32,
+    pub fuel: f32,
+    pub tyre_set: i32,
+}
+
+/// Pass through the pit lane from the pit entry to the pit exit
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PitStop {
+    /// Time from the pit entry to the pit exit
+    pub lane_time: Duration,
+    /// Time standing in the pit box
+    pub stationary_time: Duration,
+    pub fuel_added: f32,
+    /// Tyre sets before and after the stop
+    pub tyre_sets: (i32, i32),
+}
+
+impl PitStop {
+    /// Car drove through the pit lane without stopping
+    pub fn is_drive_through(&self) -> bool {
+        self.stationary_time.is_zero()
+    }
+
+    pub fn tyres_changed(&self) -> bool {
+        self.tyre_sets.0 != self.tyre_sets.1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PitEvent {
+    Entered,
+    Exited(PitStop),
+}
+
+/// Pit stop in progress
+#[derive(Debug, Clone, PartialEq)]
+struct PitEntry {
+    time: Duration,
+    fuel: f32,
+    tyre_set: i32,
+    stationary_since: Option<Duration>,
+    stationary_time: Duration,
+}
+
+/// Detects pit entries and exits of the player's car.
+///
+/// The first sample only sets the state, leaving the garage at the start of a session is no pit stop,
+/// neither is leaving it after returning to the pits.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PitDetector {
+    in_pit_lane: Option<bool>,
+    /// Shared memory reported the pit lane, the broadcast location is no longer needed
+    shm_pit_lane: bool,
+    entry: Option<PitEntry>,
+}
+
+impl PitDetector {
+    pub fn update(&mut self, sample: &PitSample) -> Option<PitEvent> {
+        self.shm_pit_lane |= sample.in_pit_lane;
+        let in_pit_lane = if self.shm_pit_lane {
+            sample.in_pit_lane
+        } else {
+            sample.location == CarLocation::Pitlane
+        };
+        let was_in_pit_lane = self.in_pit_lane.replace(in_pit_lane)?;
+
+        if let Some(entry) = &mut self.entry {
+            let stationary = sample.in_pit && sample.speed_kmh < STATIONARY_SPEED;
+            match (stationary, entry.stationary_since) {
+                (true, None) => entry.stationary_since = Some(sample.time),
+                (false, Some(since)) => {
+                    entry.stationary_time += sample.time.saturating_sub(since);
+                    entry.stationary_since = None;
+                }
+                _ => {}
+            }
+        }
+
+        match (was_in_pit_lane, in_pit_lane) {
+            (false, true) if sample.speed_kmh >= ENTRY_SPEED => {
+                self.entry = Some(PitEntry {
+                    time: sample.time,
+                    fuel: sample.fuel,
+                    tyre_set: sample.tyre_set,
+                    stationary_since: None,
+                    stationary_time: Duration::ZERO,
+                });
+                Some(PitEvent::Entered)
+            }
+            (true, false) => {
+                let entry = self.entry.take()?;
+                Some(PitEvent::Exited(PitStop {
+                    lane_time: sample.time.saturating_sub(entry.time),
+                    stationary_time: entry.stationary_time,
+                    fuel_added: (sample.fuel - entry.fuel).max(0.0),
+                    tyre_sets: (entry.tyre_set, sample.tyre_set),
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    /// Car is in the pit lane or the garage
+    pub fn in_pit_lane(&self) -> bool {
+        self.in_pit_lane == Some(true)
+    }
+}
+
+#[test]
+fn test_pit_detector() {
+    let sample =
+        |secs: u64, in_pit_lane: bool, in_pit: bool, speed_kmh: f32, fuel: f32, tyre_set: i32| PitSample {
+            time: Duration::from_secs(secs),
+            in_pit_lane,
+            in_pit,
+            location: CarLocation::Track,
+            speed_kmh,
+            fuel,
+            tyre_set,
+        };
+
+    // Leaving the garage is no pit stop
+    let mut detector = PitDetector::default();
+    assert_eq!(detector.update(&sample(0, true, true, 0.0, 60.0, 1)), None);
+    assert_eq!(detector.update(&sample(10, false, false, 80.0, 60.0, 1)), None);
+
+    assert_eq!(
+        detector.update(&sample(100, true, false, 80.0, 20.0, 1)),
+        Some(PitEvent::Entered)
+    );
+    assert_eq!(detector.update(&sample(110, true, true, 0.0, 20.0, 1)), None);
+    assert_eq!(detector.update(&sample(140, true, true, 0.0, 70.0, 2)), None);
+    assert_eq!(detector.update(&sample(141, true, false, 20.0, 70.0, 2)), None);
+    let Some(PitEvent::Exited(stop)) = detector.update(&sample(160, false, false, 80.0, 70.0, 2)) else {
+        panic!("pit exit not detected");
+    };
+    assert_eq!(stop.lane_time, Duration::from_secs(60));
+    assert_eq!(stop.stationary_time, Duration::from_secs(31));
+    assert_eq!(stop.fuel_added, 50.0);
+    assert!(stop.tyres_changed());
+    assert!(!stop.is_drive_through());
+
+    // The lagging broadcast location doesn't hold the car in the pit lane once the shared memory reports it
+    let located = |secs: u64, in_pit_lane: bool, location: CarLocation| PitSample {
+        location,
+        ..sample(secs, in_pit_lane, false, 80.0, 60.0, 2)
+    };
+    assert_eq!(
+        detector.update(&located(200, true, CarLocation::Track)),
+        Some(PitEvent::Entered)
+    );
+    assert!(detector.in_pit_lane());
+    let Some(PitEvent::Exited(stop)) = detector.update(&located(225, false, CarLocation::Pitlane)) else {
+        panic!("pit exit not detected");
+    };
+    assert_eq!(stop.lane_time, Duration::from_secs(25));
+    assert!(stop.is_drive_through());
+    assert!(!stop.tyres_changed());
+    assert_eq!(detector.update(&located(230, false, CarLocation::Track)), None);
+    assert!(!detector.in_pit_lane());
+
+    // Without pit lane data from the shared memory the broadcast location is used
+    let mut detector = PitDetector::default();
+    assert_eq!(detector.update(&located(0, false, CarLocation::Track)), None);
+    assert_eq!(
+        detector.update(&located(10, false, CarLocation::Pitlane)),
+        Some(PitEvent::Entered)
+    );
+    let Some(PitEvent::Exited(stop)) = detector.update(&located(35, false, CarLocation::Track)) else {
+        panic!("pit exit not detected");
+    };
+    assert_eq!(stop.lane_time, Duration::from_secs(25));
+    assert_eq!(stop.fuel_added, 0.0);
+
+    // Returning to the pits puts the car in the garage, driving out of it is no pit stop
+    let mut detector = PitDetector::default();
+    assert_eq!(detector.update(&sample(0, false, false, 150.0, 30.0, 1)), None);
+    assert_eq!(detector.update(&sample(10, true, true, 0.0, 30.0, 1)), None);
+    assert!(detector.in_pit_lane());
+    assert_eq!(detector.update(&sample(300, true, false, 40.0, 30.0, 2)), None);
+    assert_eq!(detector.update(&sample(320, false, false, 80.0, 30.0, 2)), None);
+}
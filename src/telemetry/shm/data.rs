@@ -1,5 +1,8 @@
 use std::collections::BTreeMap;
 
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum Penalty {
     #[default]
@@ -36,7 +39,7 @@ pub enum Status {
     Pause,
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, Display, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SessionType {
     #[default]
     Unknown,
@@ -65,7 +68,7 @@ pub enum FlagType {
     Orange,
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub enum TrackGripStatus {
     #[default]
     Green,
@@ -77,7 +80,7 @@ pub enum TrackGripStatus {
     Flooded,
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub enum RainIntensity {
     #[default]
     NoRain,
@@ -88,7 +91,7 @@ pub enum RainIntensity {
     Thunderstorm,
 }
 
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Wheels<T> {
     pub front_left: T,
     pub front_right: T,
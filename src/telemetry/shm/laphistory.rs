@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use crate::telemetry::{shm::Graphics, shm::Physics, LapWheels, Wheels};
 
 #[derive(Default, Debug, Clone)]
@@ -101,6 +103,21 @@ impl LapHistory {
     avg_min_max!(f32, brake_temperature, brake_temperature);
     avg_min_max!(f32, tyre_temperature, tyre_core_temperature);
     avg_min_max!(f32, tyre_pressure, tyre_pressure);
+
+    /// Brake pad life used up between the first and last point of the history
+    pub fn pad_wear(&self) -> Wheels<f32> {
+        let (Some(first), Some(last)) = (self.h_physics.first(), self.h_physics.last()) else {
+            return Default::default();
+        };
+
+        (
+            first.wheels.front_left.pad_life - last.wheels.front_left.pad_life,
+            first.wheels.front_right.pad_life - last.wheels.front_right.pad_life,
+            first.wheels.rear_left.pad_life - last.wheels.rear_left.pad_life,
+            first.wheels.rear_right.pad_life - last.wheels.rear_right.pad_life,
+        )
+            .into()
+    }
 }
 
 impl LapWheels {
@@ -120,10 +137,11 @@ impl LapWheels {
             min: history.min_brake_temperature(),
             max: history.max_brake_temperature(),
         };
+        self.pad_wear = history.pad_wear();
     }
 }
 
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AvgMinMax<T>
 where
     T: std::fmt::Debug + Default + Clone + PartialEq,
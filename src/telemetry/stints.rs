@@ -0,0 +1,270 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+use crate::{
+    actors::ui::LapTimeData,
+    telemetry::{broadcast::LapType, pit::PitStop, LapTime},
+};
+
+/// Laps slower than the median by this many median absolute deviations are outliers
+const OUTLIER_DEVIATIONS: f64 = 3.0;
+/// Time lost against the median a lap is always allowed, so very consistent stints don't flag every lap
+const OUTLIER_MARGIN: f64 = 0.5;
+/// Share of the time lost in a single sector above which an outlier is a mistake rather than traffic
+const MISTAKE_SHARE: f64 = 0.6;
+
+/// Stint detected from the pit lane, starting on the lap the car left the pits
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stint {
+    pub first_lap: i32,
+    pub tyre_set: i32,
+    /// Stop the stint started with, `None` for the first stint of a session
+    pub pit_stop: Option<PitStop>,
+}
+
+/// Why a lap is left out of the statistics of its stint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum Outlier {
+    Invalid,
+    /// Time lost spread over the lap, e.g. following or letting cars by
+    Traffic,
+    /// Time lost mostly in one sector, e.g. an off or a lock up
+    Mistake,
+}
+
+/// Pace of the laps of a stint.
+///
+/// In- and out-laps are left out, as are outliers, the statistics cover the clean laps only.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StintStats {
+    pub first_lap: i32,
+    pub last_lap: i32,
+    /// Laps the statistics are computed from
+    pub clean_laps: usize,
+    pub mean: LapTime,
+    pub median: LapTime,
+    pub std_dev: Duration,
+    /// Change of the lap time in seconds per lap, positive when the laps get slower
+    pub degradation: f32,
+    /// Laps left out by their lap number
+    pub outliers: BTreeMap<i32, Outlier>,
+}
+
+impl StintStats {
+    /// Statistics of the laps of one stint, `None` without any clean lap
+    pub fn new(laps: &[&LapTimeData]) -> Option<Self> {
+        let mut outliers = BTreeMap::new();
+        let mut paced: Vec<&LapTimeData> = Vec::new();
+        for lap in laps {
+            if !lap.valid {
+                outliers.insert(lap.number, Outlier::Invalid);
+            } else if lap.lap_type == LapType::Regular {
+                paced.push(lap);
+            }
+        }
+
+        let times: Vec<f64> = paced.iter().map(|lap| seconds(&lap.time)).collect();
+        let typical = median(&times)?;
+        let deviation = median(
+            &times
+                .iter()
+                .map(|time| (time - typical).abs())
+                .collect::<Vec<_>>(),
+        )?;
+        let limit = typical + (OUTLIER_DEVIATIONS * deviation).max(OUTLIER_MARGIN);
+
+        // Median sectors to find out where an outlier lost its time
+        let sector_count = paced.first().map(|lap| lap.sectors.len()).unwrap_or_default();
+        let sectors: Vec<f64> = (0..sector_count)
+            .filter_map(|i| {
+                median(
+                    &paced
+                        .iter()
+                        .filter_map(|lap| lap.sectors.get(i))
+                        .map(seconds)
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+
+        let mut clean: Vec<(f64, f64)> = Vec::new();
+        for lap in &paced {
+            let time = seconds(&lap.time);
+            if time <= limit {
+                clean.push((lap.number as f64, time));
+                continue;
+            }
+
+            let lost = time - typical;
+            let worst = lap
+                .sectors
+                .iter()
+                .zip(&sectors)
+                .map(|(sector, median)| seconds(sector) - median)
+                .fold(0.0, f64::max);
+            let outlier = if worst > lost * MISTAKE_SHARE {
+                Outlier::Mistake
+            } else {
+                Outlier::Traffic
+            };
+            outliers.insert(lap.number, outlier);
+        }
+        if clean.is_empty() {
+            return None;
+        }
+
+        let n = clean.len() as f64;
+        let mean = clean.iter().map(|(_, time)| time).sum::<f64>() / n;
+        let variance = clean.iter().map(|(_, time)| (time - mean).powi(2)).sum::<f64>() / n;
+
+        // Least squares slope of the lap time over the lap number
+        let mean_lap = clean.iter().map(|(lap, _)| lap).sum::<f64>() / n;
+        let spread: f64 = clean.iter().map(|(lap, _)| (lap - mean_lap).powi(2)).sum();
+        let degradation = if spread > 0.0 {
+            clean
+                .iter()
+                .map(|(lap, time)| (lap - mean_lap) * (time - mean))
+                .sum::<f64>()
+                / spread
+        } else {
+            0.0
+        };
+
+        Some(StintStats {
+            first_lap: laps.first().map(|lap| lap.number).unwrap_or_default(),
+            last_lap: laps.last().map(|lap| lap.number).unwrap_or_default(),
+            clean_laps: clean.len(),
+            mean: lap_time(mean),
+            median: lap_time(median(&clean.iter().map(|(_, time)| *time).collect::<Vec<_>>())?),
+            std_dev: Duration::from_secs_f64(variance.sqrt()),
+            degradation: degradation as f32,
+            outliers,
+        })
+    }
+}
+
+/// Laps grouped into stints ordered by lap number, a new stint starts with each out-lap
+/// and on the first lap of each detected stint
+pub fn stint_laps<'a>(
+    laps: impl IntoIterator<Item = &'a LapTimeData>,
+    stints: &[Stint],
+) -> Vec<Vec<&'a LapTimeData>> {
+    let mut laps: Vec<&LapTimeData> = laps.into_iter().collect();
+    laps.sort_by_key(|lap| lap.number);
+
+    let mut groups: Vec<Vec<&LapTimeData>> = Vec::new();
+    for lap in laps {
+        let starts_stint =
+            lap.lap_type == LapType::Outlap || stints.iter().any(|stint| stint.first_lap == lap.number);
+        match groups.last_mut() {
+            Some(group) if !starts_stint => group.push(lap),
+            _ => groups.push(vec![lap]),
+        }
+    }
+
+    groups
+}
+
+/// Statistics of every stint of the laps with clean laps
+pub fn stint_stats<'a>(laps: impl IntoIterator<Item = &'a LapTimeData>, stints: &[Stint]) -> Vec<StintStats> {
+    stint_laps(laps, stints)
+        .iter()
+        .filter_map(|group| StintStats::new(group))
+        .collect()
+}
+
+/// Average of the clean laps of all stints
+pub fn average_lap(stints: &[StintStats]) -> Option<LapTime> {
+    let laps: usize = stints.iter().map(|stint| stint.clean_laps).sum();
+    if laps == 0 {
+        return None;
+    }
+
+    let total: f64 = stints
+        .iter()
+        .map(|stint| seconds(&stint.mean) * stint.clean_laps as f64)
+        .sum();
+    Some(lap_time(total / laps as f64))
+}
+
+fn seconds(time: &LapTime) -> f64 {
+    time.duration().as_secs_f64()
+}
+
+/// Lap time rounded to the millisecond like the ones of the game
+fn lap_time(seconds: f64) -> LapTime {
+    Duration::from_millis((seconds * 1000.0).round() as u64).into()
+}
+
+fn median(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let mut values = values.to_vec();
+    values.sort_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        Some((values[middle - 1] + values[middle]) / 2.0)
+    } else {
+        Some(values[middle])
+    }
+}
+
+#[test]
+fn test_stint_stats() {
+    let lap = |number: i32, sectors: [u64; 3], valid: bool, lap_type: LapType| LapTimeData {
+        number,
+        sectors: sectors
+            .iter()
+            .map(|millis| Duration::from_millis(*millis).into())
+            .collect(),
+        time: Duration::from_millis(sectors.iter().sum()).into(),
+        valid,
+        lap_type,
+    };
+
+    let laps = [
+        lap(1, [40_000, 40_000, 40_000], true, LapType::Outlap),
+        lap(2, [30_000, 40_000, 30_000], true, LapType::Regular),
+        lap(3, [30_100, 40_000, 30_000], true, LapType::Regular),
+        lap(4, [30_000, 43_000, 30_000], true, LapType::Regular),
+        lap(5, [30_200, 40_000, 30_000], true, LapType::Regular),
+        lap(6, [31_000, 41_000, 31_000], true, LapType::Regular),
+        lap(7, [29_000, 39_000, 29_000], false, LapType::Regular),
+        lap(8, [30_300, 40_000, 30_000], true, LapType::Regular),
+        lap(9, [30_000, 40_000, 45_000], true, LapType::Inlap),
+        lap(10, [40_000, 40_000, 40_000], true, LapType::Outlap),
+        lap(11, [30_000, 40_000, 30_000], true, LapType::Regular),
+    ];
+
+    let stints = stint_stats(&laps, &[]);
+    assert_eq!(stints.len(), 2);
+
+    let stint = &stints[0];
+    assert_eq!((stint.first_lap, stint.last_lap), (1, 9));
+    assert_eq!(stint.clean_laps, 4);
+    assert_eq!(stint.outliers.get(&4), Some(&Outlier::Mistake));
+    assert_eq!(stint.outliers.get(&6), Some(&Outlier::Traffic));
+    assert_eq!(stint.outliers.get(&7), Some(&Outlier::Invalid));
+    assert_eq!(stint.outliers.len(), 3);
+    assert_eq!(stint.mean, Duration::from_millis(100_150).into());
+    assert_eq!(stint.median, Duration::from_millis(100_150).into());
+    assert!((stint.degradation - 0.048).abs() < 0.001);
+
+    assert_eq!(stints[1].clean_laps, 1);
+    assert_eq!(stints[1].degradation, 0.0);
+    assert_eq!(average_lap(&stints), Some(Duration::from_millis(100_120).into()));
+    assert_eq!(average_lap(&[]), None);
+
+    // A stop that wasn't flagged as an out-lap still starts a stint
+    let detected = Stint {
+        first_lap: 5,
+        ..Default::default()
+    };
+    let groups = stint_laps(&laps, &[detected]);
+    assert_eq!(groups.len(), 3);
+    assert_eq!(groups[1].first().map(|lap| lap.number), Some(5));
+}
@@ -1,7 +1,12 @@
 use dioxus::prelude::*;
 
 use crate::ui::{
-    components::{fuel_calculator::FuelCalculator, laps::Laps, setups::SetupView, Settings, StatusBar},
+    components::{
+        brake_ducts::BrakeDuctView, fuel_calculator::FuelCalculator, laps::Laps, motec::MotecView,
+        personal_best::PersonalBestView, pit_loss::PitLossView, pressure_correction::PressureCorrectionView,
+        setup_diff::SetupDiffView, setup_history::SetupHistoryView, setup_sheet::SetupSheetView,
+        setups::SetupView, Settings, StatusBar,
+    },
     Route,
 };
 
@@ -42,6 +47,39 @@ pub fn Base() -> Element {
                                 "Setups"
                             }
                         }
+                        li {
+                            Link {
+                                class: if (route == Route::Sharing {}) {
+                                    "btn btn-active-primary"
+                                } else {
+                                    "btn bg-base border-base"
+                                },
+                                to: Route::Sharing {},
+                                "Sharing"
+                            }
+                        }
+                        li {
+                            Link {
+                                class: if (route == Route::BatchEditor {}) {
+                                    "btn btn-active-primary"
+                                } else {
+                                    "btn bg-base border-base"
+                                },
+                                to: Route::BatchEditor {},
+                                "Batch Edit"
+                            }
+                        }
+                        li {
+                            Link {
+                                class: if matches!(route, Route::History {} | Route::SessionView { .. }) {
+                                    "btn btn-active-primary"
+                                } else {
+                                    "btn bg-base border-base"
+                                },
+                                to: Route::History {},
+                                "History"
+                            }
+                        }
                         // DebugLink { route: route.clone() }
                     }
                 }
@@ -93,7 +131,12 @@ pub fn Home() -> Element {
     rsx! {
         div { class: "grid grid-cols-[auto_max-content] gap-2",
             Laps {}
-            div { class: "grid grid-rows-[max-content_1fr] gap-2", FuelCalculator {} }
+            div { class: "grid grid-rows-[max-content_max-content_max-content_max-content_1fr] gap-2",
+                FuelCalculator {}
+                PersonalBestView {}
+                PitLossView {}
+                MotecView {}
+            }
         }
     }
 }
@@ -102,8 +145,19 @@ pub fn Home() -> Element {
 pub fn Setups() -> Element {
     rsx! {
         div { class: "grid grid-cols-[auto_max-content] gap-2",
-            SetupView {}
-            div { class: "grid grid-rows-[max-content_1fr] gap-2", FuelCalculator {} }
+            div { class: "grid grid-rows-[minmax(0,_1fr)_minmax(0,_1fr)] gap-2",
+                SetupView {}
+                div { class: "grid grid-cols-2 gap-2 min-h-0",
+                    SetupDiffView {}
+                    SetupHistoryView {}
+                }
+            }
+            div { class: "grid grid-rows-[max-content_max-content_max-content_1fr] gap-2",
+                FuelCalculator {}
+                PressureCorrectionView {}
+                BrakeDuctView {}
+                SetupSheetView {}
+            }
         }
     }
 }
@@ -0,0 +1,224 @@
+use actix::prelude::*;
+use dioxus::prelude::*;
+
+use crate::{
+    actors::{
+        setup_manager::{
+            fields, BatchChange, BatchEdit, BatchOperation, BatchPreview, Setup, SetupChange, TemplateRef,
+        },
+        ui::Setups,
+    },
+    ui::components::{setup_diff::FieldChangeRow, Settings},
+};
+
+#[component]
+pub fn BatchEditor() -> Element {
+    let router: Addr<crate::actors::Router> = use_context();
+    use_hook(|| router.do_send(SetupChange::RefreshLibrary));
+
+    rsx! {
+        div { class: "grid grid-cols-2 gap-2 min-h-0",
+            BatchEditView {}
+            BatchPreviewView {}
+        }
+    }
+}
+
+#[component]
+fn BatchEditView() -> Element {
+    let setups: SyncSignal<Setups> = use_context();
+    let router: Addr<crate::actors::Router> = use_context();
+    let mut car = use_signal(String::new);
+    let mut path = use_signal(String::new);
+    let mut add = use_signal(|| true);
+    let mut value = use_signal(|| 1i64);
+    let mut selection: Signal<Vec<TemplateRef>> = use_signal(Vec::new);
+
+    let library = setups.read().library.clone();
+    let mut cars: Vec<String> = library.tracks.iter().map(|track| track.car.clone()).collect();
+    cars.dedup();
+
+    // Every template of the selected car, grouped by track
+    let tracks: Vec<_> = library
+        .templates
+        .into_iter()
+        .filter(|(car_track, _)| car_track.car == car())
+        .collect();
+    let options: Vec<(String, String)> = fields(&Setup::default())
+        .into_iter()
+        .filter(|field| field.is_clicks())
+        .map(|field| (field.path, field.label))
+        .collect();
+
+    let operation = if add() {
+        BatchOperation::Add(value())
+    } else {
+        BatchOperation::Set(value())
+    };
+
+    rsx! {
+        div { class: "grid grid-rows-[min-content_min-content_min-content_auto_min-content] p-2 bg-base rounded-lg shadow-lg min-h-0",
+            div { class: "grid grid-cols-1",
+                h1 { class: "text-xl pb-2 justify-self-center", "Batch Edit" }
+            }
+            select {
+                class: "select select-bordered select-sm mb-2",
+                oninput: move |event| {
+                    car.set(event.value());
+                    selection.set(Vec::new());
+                },
+                option { value: "", selected: car().is_empty(), "Select a car" }
+                { cars.iter().map(|name| rsx! {
+                    option { value: "{name}", selected: *name == car(), "{name}" }
+                })}
+            }
+            div { class: "grid grid-cols-[1fr_min-content_5rem] gap-2 items-center mb-2",
+                select {
+                    class: "select select-bordered select-sm",
+                    oninput: move |event| path.set(event.value()),
+                    option { value: "", selected: path().is_empty(), "Select a value" }
+                    { options.iter().map(|(option, label)| rsx! {
+                        option { value: "{option}", selected: *option == path(), "{label}" }
+                    })}
+                }
+                select {
+                    class: "select select-bordered select-sm",
+                    oninput: move |event| add.set(event.value() == "add"),
+                    option { value: "add", selected: add(), "Add" }
+                    option { value: "set", selected: !add(), "Set" }
+                }
+                input {
+                    r#type: "number",
+                    class: "input input-sm input-bordered w-20",
+                    step: "1",
+                    value: "{value}",
+                    oninput: move |event| {
+                        if let Ok(clicks) = event.value().parse() {
+                            value.set(clicks);
+                        }
+                    },
+                }
+            }
+            div { class: "grid auto-rows-min gap-2 overflow-y-auto",
+                if tracks.is_empty() {
+                    div { class: "divider divider-vertical mx-2", "No Templates" }
+                }
+                { tracks.into_iter().map(|(car_track, names)| {
+                    let label = car_track.track.clone();
+                    rsx! {
+                        div { class: "grid gap-1 bg-surface0 rounded-md p-2",
+                            span { "{label}" }
+                            { names.into_iter().map(|name| {
+                                let template = TemplateRef { car_track: car_track.clone(), name: name.clone() };
+                                let checked = selection.read().contains(&template);
+                                rsx! {
+                                    label { class: "grid grid-cols-[min-content_1fr] gap-2 items-center text-sm cursor-pointer",
+                                        input {
+                                            r#type: "checkbox",
+                                            class: "checkbox checkbox-sm",
+                                            checked,
+                                            onchange: move |_| {
+                                                let mut selection = selection.write();
+                                                match selection.iter().position(|t| *t == template) {
+                                                    Some(index) => {
+                                                        selection.remove(index);
+                                                    }
+                                                    None => selection.push(template.clone()),
+                                                }
+                                            }
+                                        }
+                                        span { "{name}" }
+                                    }
+                                }
+                            })}
+                        }
+                    }
+                })}
+            }
+            button {
+                class: "btn btn-sm mt-2",
+                disabled: path().is_empty() || selection.read().is_empty(),
+                onclick: move |_| {
+                    let edit = BatchEdit {
+                        path: path(),
+                        operation,
+                        templates: selection(),
+                    };
+                    router.do_send(SetupChange::PreviewBatch(Some(edit)));
+                },
+                "Preview"
+            }
+        }
+    }
+}
+
+#[component]
+fn BatchPreviewView() -> Element {
+    let setups: SyncSignal<Setups> = use_context();
+    let router: Addr<crate::actors::Router> = use_context();
+    let settings: Signal<Settings> = use_context();
+
+    let Some(preview) = setups.read().batch.clone() else {
+        return rsx! {
+            div { class: "grid p-2 bg-base rounded-lg shadow-lg",
+                div { class: "divider divider-vertical h-full mx-2 justify-self-center",
+                    "No Batch Edit"
+                }
+            }
+        };
+    };
+
+    let valid = preview.is_valid();
+    let BatchPreview { edit, changes } = preview;
+
+    rsx! {
+        div { class: "grid grid-rows-[min-content_auto] p-2 bg-base rounded-lg shadow-lg min-h-0",
+            div { class: "grid grid-cols-[1fr_max-content] gap-2 items-center pb-2",
+                h1 { class: "text-xl", "Preview" }
+                ul { class: "menu menu-horizontal rounded-box gap-2 p-0",
+                    li {
+                        button {
+                            class: "btn btn-sm",
+                            disabled: !valid,
+                            onclick: move |_| {
+                                let author = settings.read().author.clone();
+                                router.do_send(SetupChange::ApplyBatch(edit.clone(), author));
+                            },
+                            "Apply"
+                        }
+                    }
+                    li {
+                        button {
+                            class: "btn btn-sm",
+                            onclick: move |_| router.do_send(SetupChange::PreviewBatch(None)),
+                            "Cancel"
+                        }
+                    }
+                }
+            }
+            div { class: "grid auto-rows-min gap-2 overflow-y-auto",
+                { changes.into_iter().map(|change| rsx! { BatchChangeView { change } }) }
+            }
+        }
+    }
+}
+
+#[component]
+fn BatchChangeView(change: BatchChange) -> Element {
+    let label = format!("{} - {}", change.template.car_track.track, change.template.name);
+
+    rsx! {
+        div { class: "grid gap-1 bg-surface0 rounded-md p-2",
+            span { "{label}" }
+            if let Some(error) = change.error {
+                span { class: "text-sm text-red", "{error}" }
+            } else if change.changes.is_empty() {
+                span { class: "text-sm", "Unchanged" }
+            }
+            if let Some(warning) = change.warning {
+                span { class: "text-sm text-warning", "{warning}" }
+            }
+            { change.changes.into_iter().map(|change| rsx! { FieldChangeRow { change } }) }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use actix::prelude::*;
+use dioxus::prelude::*;
+
+use crate::actors::{
+    setup_manager::{BrakeTarget, SetupChange},
+    ui::Setups,
+};
+
+#[component]
+pub fn BrakeDuctView() -> Element {
+    let setups: SyncSignal<Setups> = use_context();
+    let router: Addr<crate::actors::Router> = use_context();
+
+    let Some(recommendation) = setups.read().brake_ducts.clone() else {
+        return rsx! {
+            div { class: "grid auto-rows-min bg-base rounded-lg shadow-lg",
+                div { class: "label px-0 py-2",
+                    span { class: "label-text text-nowrap px-4", "Brake Ducts" }
+                    span { class: "label-text text-nowrap px-4 text-red", "Drive Laps" }
+                }
+            }
+        };
+    };
+
+    let front = BrakeTarget::for_pad(recommendation.pads[0]);
+    let rear = BrakeTarget::for_pad(recommendation.pads[1]);
+
+    rsx! {
+        div { class: "grid auto-rows-min bg-base rounded-lg shadow-lg",
+            div { class: "label px-0 py-2 border-b-[1px] border-crust",
+                span { class: "label-text text-nowrap px-4", "Brake Ducts" }
+                span { class: "label-text text-nowrap px-4",
+                    "Pads {recommendation.pads[0] + 1} / {recommendation.pads[1] + 1}"
+                }
+            }
+            div { class: "grid gap-2 p-4 border-b-[1px] border-crust",
+                BrakeAxle {
+                    name: "Front",
+                    min: front.min,
+                    max: front.max,
+                    temperature: recommendation.temperature[0],
+                    peak: recommendation.peak[0],
+                    clicks: recommendation.clicks[0]
+                }
+                BrakeAxle {
+                    name: "Rear",
+                    min: rear.min,
+                    max: rear.max,
+                    temperature: recommendation.temperature[1],
+                    peak: recommendation.peak[1],
+                    clicks: recommendation.clicks[1]
+                }
+            }
+            div { class: "label px-0 py-2",
+                span { class: "label-text text-nowrap px-4", "Forecast" }
+                span { class: "label-text text-nowrap px-4", "{recommendation.ambient_change:+.1}°C" }
+            }
+            ul { class: "menu menu-horizontal rounded-box gap-2 w-max pt-0",
+                li {
+                    button {
+                        class: "btn btn-sm",
+                        disabled: recommendation.is_empty(),
+                        onclick: move |_| router.do_send(SetupChange::ApplyBrakeDucts),
+                        "Apply to Race Setups"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn BrakeAxle(name: String, min: f32, max: f32, temperature: f32, peak: f32, clicks: i32) -> Element {
+    let color = if clicks > 0 {
+        "text-blue"
+    } else if clicks < 0 {
+        "text-red"
+    } else {
+        ""
+    };
+
+    rsx! {
+        div { class: "grid grid-cols-[1fr_1fr_1fr_min-content] gap-2 bg-surface0 rounded-md px-2 py-1",
+            span { "{name}" }
+            span { title: "{min:.0} - {max:.0}°C", "{temperature:.0}°C" }
+            span { title: "Peak", "{peak:.0}°C" }
+            span { class: "justify-self-end {color}", "{clicks:+}" }
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use actix::prelude::*;
+use chrono::NaiveDate;
+use dioxus::prelude::*;
+
+use crate::{
+    actors::{
+        sessions::{Session, SessionFilter, SessionQuery},
+        ui::SessionHistory,
+    },
+    ui::{components::laps::LapsTable, Route},
+};
+
+/// Stored sessions, filtered by car, track, session type and date
+#[component]
+pub fn History() -> Element {
+    let history: SyncSignal<SessionHistory> = use_context();
+    let router: Addr<crate::actors::Router> = use_context();
+    use_hook(|| router.do_send(SessionQuery::List));
+
+    let mut car = use_signal(String::new);
+    let mut track = use_signal(String::new);
+    let mut session_type = use_signal(String::new);
+    let mut from = use_signal(String::new);
+    let mut to = use_signal(String::new);
+
+    let sessions = history.read().sessions.clone();
+    let mut cars: Vec<String> = sessions.iter().map(|session| session.car.clone()).collect();
+    cars.sort();
+    cars.dedup();
+    let mut tracks: Vec<String> = sessions.iter().map(|session| session.track.clone()).collect();
+    tracks.sort();
+    tracks.dedup();
+    let mut session_types: Vec<String> = sessions
+        .iter()
+        .map(|session| session.session_type.to_string())
+        .collect();
+    session_types.sort();
+    session_types.dedup();
+
+    let filter = SessionFilter {
+        car: Some(car()).filter(|car| !car.is_empty()),
+        track: Some(track()).filter(|track| !track.is_empty()),
+        session_type: sessions
+            .iter()
+            .map(|session| session.session_type)
+            .find(|t| t.to_string() == session_type()),
+        from: NaiveDate::parse_from_str(&from(), "%Y-%m-%d").ok(),
+        to: NaiveDate::parse_from_str(&to(), "%Y-%m-%d").ok(),
+    };
+    let filtered: Vec<_> = sessions
+        .into_iter()
+        .filter(|session| filter.matches(session))
+        .collect();
+
+    rsx! {
+        div { class: "grid grid-rows-[min-content_minmax(0,_1fr)] p-2 bg-base rounded-lg shadow-lg min-h-0",
+            div { class: "grid grid-cols-[1fr_1fr_1fr_max-content_max-content] gap-2 pb-2",
+                select {
+                    class: "select select-bordered select-sm",
+                    oninput: move |event| car.set(event.value()),
+                    option { value: "", selected: car().is_empty(), "All Cars" }
+                    { cars.iter().map(|name| rsx! {
+                        option { value: "{name}", selected: *name == car(), "{name}" }
+                    })}
+                }
+                select {
+                    class: "select select-bordered select-sm",
+                    oninput: move |event| track.set(event.value()),
+                    option { value: "", selected: track().is_empty(), "All Tracks" }
+                    { tracks.iter().map(|name| rsx! {
+                        option { value: "{name}", selected: *name == track(), "{name}" }
+                    })}
+                }
+                select {
+                    class: "select select-bordered select-sm",
+                    oninput: move |event| session_type.set(event.value()),
+                    option { value: "", selected: session_type().is_empty(), "All Sessions" }
+                    { session_types.iter().map(|name| rsx! {
+                        option { value: "{name}", selected: *name == session_type(), "{name}" }
+                    })}
+                }
+                input {
+                    r#type: "date",
+                    class: "input input-bordered input-sm",
+                    title: "From",
+                    value: "{from}",
+                    oninput: move |event| from.set(event.value()),
+                }
+                input {
+                    r#type: "date",
+                    class: "input input-bordered input-sm",
+                    title: "To",
+                    value: "{to}",
+                    oninput: move |event| to.set(event.value()),
+                }
+            }
+            { if filtered.is_empty() {
+                rsx! {
+                    div { class: "divider divider-vertical h-full mx-2 justify-self-center",
+                        "No Sessions Recorded"
+                    }
+                }
+            } else {
+                rsx! {
+                    div { class: "scrollable",
+                        table { class: "table table-zebra table-pin-rows table-sm",
+                            thead { class: "text-md text-text",
+                                tr {
+                                    th { "Date" }
+                                    th { "Car" }
+                                    th { "Track" }
+                                    th { "Session" }
+                                    th { "Laps" }
+                                    th { "Best Lap" }
+                                    th {}
+                                }
+                            }
+                            tbody {
+                                { filtered.into_iter().map(|session| {
+                                    let started = session.started.format("%Y-%m-%d %H:%M").to_string();
+                                    let best_lap = session.best_lap.map(|lap| lap.to_string()).unwrap_or_default();
+                                    let id = session.id.clone();
+                                    let router = router.clone();
+                                    rsx! {
+                                        tr { key: "{session.id}",
+                                            td {
+                                                Link {
+                                                    class: "link link-hover",
+                                                    to: Route::SessionView { id: session.id.clone() },
+                                                    "{started}"
+                                                }
+                                            }
+                                            td { "{session.car}" }
+                                            td { "{session.track}" }
+                                            td { "{session.session_type}" }
+                                            td { "{session.laps}" }
+                                            td { "{best_lap}" }
+                                            td {
+                                                button {
+                                                    class: "btn btn-sm",
+                                                    onclick: move |_| router.do_send(SessionQuery::Remove(id.clone())),
+                                                    "Remove"
+                                                }
+                                            }
+                                        }
+                                    }
+                                })}
+                            }
+                        }
+                    }
+                }
+            }}
+        }
+    }
+}
+
+/// Laps, fuel usage and weather of a stored session
+#[component]
+pub fn SessionView(id: String) -> Element {
+    let history: SyncSignal<SessionHistory> = use_context();
+    let router: Addr<crate::actors::Router> = use_context();
+    // Opening another session from the history keeps this component, only its id changes
+    use_effect(use_reactive!(|id| router.do_send(SessionQuery::Open(id))));
+
+    // The history still holds the last opened session until this one is loaded
+    let Some(session) = history.read().session.clone().filter(|session| session.id == id) else {
+        return rsx! {
+            div { class: "grid p-2 bg-base rounded-lg shadow-lg",
+                div { class: "divider divider-vertical h-full mx-2 justify-self-center",
+                    "No Session Loaded"
+                }
+            }
+        };
+    };
+
+    let laps = crate::actors::ui::Laps {
+        times: session.laps.clone().into_iter().collect(),
+        wheels: session.wheels.clone().into_iter().collect(),
+        stints: session.stints.clone(),
+    };
+
+    rsx! {
+        div { class: "grid grid-cols-[auto_max-content] gap-2",
+            LapsTable { laps }
+            div { class: "grid grid-rows-[max-content_max-content_1fr] gap-2",
+                SessionFuel { session: session.clone() }
+                SessionWeather { session }
+            }
+        }
+    }
+}
+
+#[component]
+fn SessionFuel(session: Session) -> Element {
+    let started = session.started.format("%Y-%m-%d %H:%M").to_string();
+    let duration = session
+        .ended
+        .map(|ended| format!("{} min", (ended - session.started).num_minutes()))
+        .unwrap_or_else(|| "Not Ended".to_owned());
+    let summary = session.summary();
+    let best_lap = summary
+        .best_lap
+        .map(|lap| lap.to_string())
+        .unwrap_or_else(|| "-".to_owned());
+    let average_lap = session
+        .average_lap()
+        .map(|lap| lap.to_string())
+        .unwrap_or_else(|| "-".to_owned());
+    let fuel_per_lap = session
+        .fuel_per_lap()
+        .map(|fuel| format!("{fuel:.2} l"))
+        .unwrap_or_else(|| "-".to_owned());
+
+    rsx! {
+        div { class: "grid auto-rows-min bg-base rounded-lg shadow-lg",
+            div { class: "label px-0 py-2 border-b-[1px] border-crust",
+                span { class: "label-text text-nowrap px-4", "{session.session_type}" }
+                span { class: "label-text text-nowrap px-4", "{started}" }
+            }
+            div { class: "label px-0 py-2 border-b-[1px] border-crust",
+                span { class: "label-text text-nowrap px-4", "{session.car}" }
+                span { class: "label-text text-nowrap px-4", "{session.track}" }
+            }
+            div { class: "label px-0 py-2 border-b-[1px] border-crust",
+                span { class: "label-text text-nowrap px-4", "Duration" }
+                span { class: "label-text text-nowrap px-4", "{duration}" }
+            }
+            div { class: "label px-0 py-2 border-b-[1px] border-crust",
+                span { class: "label-text text-nowrap px-4", "Laps" }
+                span { class: "label-text text-nowrap px-4", "{summary.laps}" }
+            }
+            div { class: "label px-0 py-2 border-b-[1px] border-crust",
+                span { class: "label-text text-nowrap px-4", "Best Lap" }
+                span { class: "label-text text-nowrap px-4", "{best_lap}" }
+            }
+            div { class: "label px-0 py-2 border-b-[1px] border-crust",
+                span { class: "label-text text-nowrap px-4", "Avg Lap" }
+                span { class: "label-text text-nowrap px-4", "{average_lap}" }
+            }
+            div { class: "label px-0 py-2",
+                span { class: "label-text text-nowrap px-4", "Fuel/Lap" }
+                span { class: "label-text text-nowrap px-4", "{fuel_per_lap}" }
+            }
+        }
+    }
+}
+
+#[component]
+fn SessionWeather(session: Session) -> Element {
+    if session.weather.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div { class: "bg-base rounded-lg shadow-lg scrollable h-auto",
+            table { class: "table table-zebra table-pin-rows table-sm",
+                thead { class: "text-md text-text",
+                    tr {
+                        th { "Time" }
+                        th { "Air" }
+                        th { "Track" }
+                        th { "Clouds" }
+                        th { "Rain" }
+                    }
+                }
+                tbody {
+                    { session.weather.iter().map(|(time, weather)| {
+                        let time = time.format("%H:%M").to_string();
+                        // Clouds and rain are reported in tenths
+                        let clouds = weather.clouds as u32 * 10;
+                        let rain = weather.rain_level as u32 * 10;
+                        rsx! {
+                            tr {
+                                td { "{time}" }
+                                td { "{weather.ambient_temp} C" }
+                                td { "{weather.track_temp} C" }
+                                td { "{clouds}%" }
+                                td { "{rain}%" }
+                            }
+                        }
+                    })}
+                }
+            }
+        }
+    }
+}
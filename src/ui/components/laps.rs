@@ -1,19 +1,45 @@
+use std::collections::BTreeSet;
+
 use dioxus::prelude::*;
 
-use crate::telemetry::broadcast::LapType;
+use crate::{
+    actors::{setup_manager::PersonalBest, ui::SessionInfo},
+    telemetry::{
+        broadcast::LapType,
+        stints::{stint_laps, Stint, StintStats},
+    },
+};
 
 #[component]
 pub fn Laps() -> Element {
     let state: SyncSignal<crate::actors::ui::Laps> = use_context();
+    let info: SyncSignal<SessionInfo> = use_context();
+    let laps = state.read().clone();
+    let personal_best = info.read().personal_best.clone();
+
+    rsx! {
+        LapsTable { laps, personal_best }
+    }
+}
 
-    if state.read().iter().filter_map(|lap| lap).next().is_none() {
+/// Laps with their tyre and brake stats grouped into collapsible stints, sectors matching the
+/// personal best are purple and the ones matching the session best green
+#[component]
+pub fn LapsTable(laps: crate::actors::ui::Laps, personal_best: Option<PersonalBest>) -> Element {
+    // Stints folded away by their first lap
+    let collapsed: Signal<BTreeSet<i32>> = use_signal(BTreeSet::new);
+
+    if laps.iter().filter_map(|lap| lap).next().is_none() {
         rsx! {
             div { class: "grid bg-base rounded-lg shadow-lg overflow-auto h-auto",
                 div { class: "justify-self-center align-middle", "No Laps Recorded" }
             }
         }
     } else {
-        let sectors = state.read().sectors();
+        let sectors = laps.sectors();
+        let personal_best = personal_best.unwrap_or_default();
+        let session_best = PersonalBest::from_laps(laps.times.values());
+        let groups = stint_laps(laps.times.values(), &laps.stints);
 
         rsx! {
             div { class: "bg-base rounded-lg shadow-lg scrollable h-auto",
@@ -68,22 +94,43 @@ pub fn Laps() -> Element {
                         }
                     }
                     tbody {
-                        {
-                            state.read().iter().filter_map(|lap| lap).map(|(times, lap)| {
+                        { groups.iter().enumerate().map(|(index, group)| {
+                            let first_lap = group.first().map(|lap| lap.number).unwrap_or_default();
+                            let last_lap = group.last().map(|lap| lap.number).unwrap_or_default();
+                            let stats = StintStats::new(group);
+                            let stint = laps
+                                .stints
+                                .iter()
+                                .find(|stint| (first_lap..=last_lap).contains(&stint.first_lap))
+                                .cloned();
+                            let hidden = collapsed.read().contains(&first_lap);
+                            let rows = group.iter().filter(|_| !hidden).filter_map(|lap| laps.get(lap.number)).map(|(times, lap)| {
+                                let outlier = stats.as_ref().and_then(|stats| stats.outliers.get(&times.number)).copied();
+                                let counted = times.valid && times.lap_type == LapType::Regular;
+                                let time_color = if !times.valid {
+                                    "text-red"
+                                } else {
+                                    best_color(
+                                        counted && personal_best.is_best_lap(&times.time),
+                                        counted && session_best.is_best_lap(&times.time),
+                                    )
+                                };
                                 rsx! {
-                                    { if times.lap_type == LapType::Outlap {
-                                        rsx! {
-                                            tr {
-                                                th { "Pit" }
-                                            }
-                                        }
-                                    } else { rsx! {} }}
                                     tr {
-                                        th { "{times.number}" }
-                                        td { class: if !times.valid { "text-red" }, "{times.time}" }
-                                        { times.sectors.iter().map(|sector| {
+                                        th { title: outlier.map(|outlier| outlier.to_string()),
+                                            "{times.number}"
+                                            { outlier.map(|outlier| rsx! {
+                                                span { class: "text-xs font-normal text-peach pl-1", "{outlier}" }
+                                            })}
+                                        }
+                                        td { class: time_color, "{times.time}" }
+                                        { times.sectors.iter().enumerate().map(|(i, sector)| {
+                                            let color = best_color(
+                                                counted && personal_best.is_best_sector(i, sector),
+                                                counted && session_best.is_best_sector(i, sector),
+                                            );
                                             rsx! {
-                                                td { "{sector}" }
+                                                td { class: color, "{sector}" }
                                             }
                                         })}
                                         td {
@@ -160,8 +207,19 @@ pub fn Laps() -> Element {
                                         }
                                     }
                                 }
-                            })
-                        }
+                            });
+                            rsx! {
+                                StintRow {
+                                    number: index + 1,
+                                    first_lap,
+                                    stats: stats.clone(),
+                                    stint,
+                                    columns: sectors + 10,
+                                    collapsed,
+                                }
+                                { rows }
+                            }
+                        })}
                     }
                 }
             }
@@ -169,6 +227,80 @@ pub fn Laps() -> Element {
     }
 }
 
+/// Header of a stint with its pit stop and the pace of its clean laps, clicking it folds the laps
+#[component]
+fn StintRow(
+    number: usize,
+    first_lap: i32,
+    stats: Option<StintStats>,
+    stint: Option<Stint>,
+    columns: usize,
+    collapsed: Signal<BTreeSet<i32>>,
+) -> Element {
+    let mut collapsed = collapsed;
+    let arrow = if collapsed.read().contains(&first_lap) {
+        "▸"
+    } else {
+        "▾"
+    };
+
+    let tyre_set = stint.as_ref().map(|stint| format!("Set {}", stint.tyre_set));
+    let pit_stop = stint.and_then(|stint| stint.pit_stop).map(|pit_stop| {
+        let lane = pit_stop.lane_time.as_secs_f32();
+        if pit_stop.is_drive_through() {
+            format!("Drive through {lane:.1} s")
+        } else {
+            let stationary = pit_stop.stationary_time.as_secs_f32();
+            format!(
+                "Stop {lane:.1} s, {stationary:.1} s stationary, {:+.1} l",
+                pit_stop.fuel_added
+            )
+        }
+    });
+
+    rsx! {
+        tr {
+            class: "bg-surface0 cursor-pointer",
+            onclick: move |_| {
+                let mut collapsed = collapsed.write();
+                if !collapsed.remove(&first_lap) {
+                    collapsed.insert(first_lap);
+                }
+            },
+            th { class: "text-nowrap", "{arrow} Stint {number}" }
+            td { colspan: "{columns}",
+                div { class: "flex gap-6 text-xs",
+                    { tyre_set.map(|tyre_set| rsx! { span { "{tyre_set}" } }) }
+                    { pit_stop.map(|pit_stop| rsx! { span { "{pit_stop}" } }) }
+                    { stats.map(|stats| {
+                        let std_dev = format!("{:.3}", stats.std_dev.as_secs_f32());
+                        let degradation = format!("{:+.3}", stats.degradation);
+                        let outliers = stats.outliers.len();
+                        rsx! {
+                            span { "Mean {stats.mean}" }
+                            span { "Median {stats.median}" }
+                            span { "σ {std_dev} s" }
+                            span { title: "Change of the lap time per lap", "Deg {degradation} s/lap" }
+                            span { "{stats.clean_laps} clean laps, {outliers} outliers" }
+                        }
+                    })}
+                }
+            }
+        }
+    }
+}
+
+/// Purple for a personal best, green for a session best
+fn best_color(personal_best: bool, session_best: bool) -> &'static str {
+    if personal_best {
+        "text-mauve"
+    } else if session_best {
+        "text-green"
+    } else {
+        ""
+    }
+}
+
 fn factor_range(value: f32, min: f32, max: f32, ideal_min: f32, ideal_max: f32) -> f32 {
     let value = value.clamp(min, max);
     if value >= ideal_min && value <= ideal_max {
@@ -1,9 +1,21 @@
 mod base;
+pub mod batch_edit;
+pub mod brake_ducts;
 // mod debug;
 pub mod fuel_calculator;
+pub mod history;
 pub mod laps;
+pub mod motec;
+pub mod personal_best;
+pub mod pit_loss;
+pub mod pressure_correction;
 mod settings;
+pub mod setup_diff;
+pub mod setup_editor;
+pub mod setup_history;
+pub mod setup_sheet;
 pub mod setups;
+pub mod sharing;
 mod status_bar;
 mod theme;
 
@@ -0,0 +1,30 @@
+use dioxus::prelude::*;
+
+use crate::actors::{motec::archive_folder, ui::SessionInfo};
+
+/// MoTeC files archived for the laps of the last session
+#[component]
+pub fn MotecView() -> Element {
+    let info: SyncSignal<SessionInfo> = use_context();
+
+    let Some(session) = info.read().motec.clone() else {
+        return rsx! {};
+    };
+    let folder = session.folder(&archive_folder()).display().to_string();
+    let files = session.files.len();
+    let laps = session.laps.len();
+
+    rsx! {
+        div { class: "grid auto-rows-min bg-base rounded-lg shadow-lg",
+            div { class: "label px-0 py-2 border-b-[1px] border-crust",
+                span { class: "label-text text-nowrap px-4", "MoTeC" }
+                span { class: "label-text text-nowrap px-4", "{session.session_type}" }
+            }
+            div { class: "label px-0 py-2 border-b-[1px] border-crust",
+                span { class: "label-text text-nowrap px-4", "Files" }
+                span { class: "label-text text-nowrap px-4", "{files} files, {laps} laps" }
+            }
+            div { class: "px-4 py-2 text-sm break-all", title: "{folder}", "{folder}" }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use dioxus::prelude::*;
+
+use crate::actors::ui::SessionInfo;
+
+/// Personal best, theoretical best and consistency of the car on the track
+#[component]
+pub fn PersonalBestView() -> Element {
+    let info: SyncSignal<SessionInfo> = use_context();
+
+    let best = info.read().personal_best.clone();
+    let Some(lap) = best.lap.clone() else {
+        return rsx! {};
+    };
+    let theoretical = best
+        .theoretical_best()
+        .map(|lap| lap.to_string())
+        .unwrap_or_default();
+    let consistency = best
+        .consistency()
+        .map(|deviation| format!("± {:.3} s", deviation.as_secs_f32()))
+        .unwrap_or_default();
+    let laps = best.recent.len();
+
+    rsx! {
+        div { class: "grid auto-rows-min bg-base rounded-lg shadow-lg",
+            div { class: "label px-0 py-2 border-b-[1px] border-crust",
+                span { class: "label-text text-nowrap px-4", "Personal Best" }
+                span { class: "label-text text-nowrap px-4 text-mauve", "{lap.time}" }
+            }
+            div { class: "label px-0 py-2 border-b-[1px] border-crust",
+                span { class: "label-text text-nowrap px-4", "Theoretical Best" }
+                span { class: "label-text text-nowrap px-4", "{theoretical}" }
+            }
+            { best.sectors.iter().enumerate().map(|(i, sector)| {
+                let name = format!("S{}", i + 1);
+                rsx! {
+                    div { class: "label px-0 py-2 border-b-[1px] border-crust",
+                        span { class: "label-text text-nowrap px-4", "{name}" }
+                        span { class: "label-text text-nowrap px-4 text-mauve", "{sector}" }
+                    }
+                }
+            })}
+            div { class: "label px-0 py-2",
+                span { class: "label-text text-nowrap px-4", title: "Over the last {laps} laps", "Consistency" }
+                span { class: "label-text text-nowrap px-4", "{consistency}" }
+            }
+        }
+    }
+}
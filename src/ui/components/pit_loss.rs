@@ -0,0 +1,51 @@
+use dioxus::prelude::*;
+
+use crate::actors::{pit_loss::RunningAverage, ui::SessionInfo};
+
+/// Average time spent and lost in the pits on the track
+#[component]
+pub fn PitLossView() -> Element {
+    let info: SyncSignal<SessionInfo> = use_context();
+
+    let Some(pit_loss) = info.read().pit_loss.clone() else {
+        return rsx! {};
+    };
+    let rows = [
+        ("Drive Through", "Time in the pit lane", pit_loss.drive_through),
+        ("Pit Stop", "Time in the pit lane", pit_loss.stop),
+        ("Stationary", "Time standing in the pit box", pit_loss.stationary),
+        (
+            "Drive Through Loss",
+            "In- and out-lap against two laps at pace without the time standing in the box",
+            pit_loss.lane_loss,
+        ),
+        (
+            "Pit Stop Loss",
+            "In- and out-lap of stops against two laps at pace",
+            pit_loss.stop_loss,
+        ),
+    ];
+
+    rsx! {
+        div { class: "grid auto-rows-min bg-base rounded-lg shadow-lg",
+            { rows.into_iter().map(|(name, description, average)| rsx! {
+                PitLossRow { name, description, average }
+            })}
+        }
+    }
+}
+
+#[component]
+fn PitLossRow(name: &'static str, description: &'static str, average: RunningAverage) -> Element {
+    let Some(seconds) = average.get() else {
+        return rsx! {};
+    };
+    let count = average.count;
+
+    rsx! {
+        div { class: "label px-0 py-2 border-b-[1px] border-crust",
+            span { class: "label-text text-nowrap px-4", title: "{description}", "{name}" }
+            span { class: "label-text text-nowrap px-4", title: "Average of {count} stops", "{seconds:.1} s" }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use actix::prelude::*;
+use dioxus::prelude::*;
+
+use crate::{
+    actors::{
+        setup_manager::{PressureTarget, SetupChange},
+        ui::Setups,
+    },
+    ui::components::Settings,
+};
+
+#[component]
+pub fn PressureCorrectionView() -> Element {
+    let setups: SyncSignal<Setups> = use_context();
+    let router: Addr<crate::actors::Router> = use_context();
+    let settings: Signal<Settings> = use_context();
+
+    let Some(correction) = setups.read().pressure_correction.clone() else {
+        return rsx! {
+            div { class: "grid auto-rows-min bg-base rounded-lg shadow-lg",
+                div { class: "label px-0 py-2",
+                    span { class: "label-text text-nowrap px-4", "Pressures" }
+                    span { class: "label-text text-nowrap px-4 text-red", "Drive Laps" }
+                }
+            }
+        };
+    };
+
+    let target = PressureTarget::for_compound(correction.compound);
+    let measured = correction.measured;
+    let clicks = correction.clicks;
+    let template_router = router.clone();
+
+    rsx! {
+        div { class: "grid auto-rows-min bg-base rounded-lg shadow-lg",
+            div { class: "label px-0 py-2 border-b-[1px] border-crust",
+                span { class: "label-text text-nowrap px-4", "Pressures" }
+                span { class: "label-text text-nowrap px-4", "{target.min:.1} - {target.max:.1} psi" }
+            }
+            div { class: "grid grid-cols-2 gap-2 p-4 border-b-[1px] border-crust",
+                PressureCorner { measured: measured.front_left, clicks: clicks[0] }
+                PressureCorner { measured: measured.front_right, clicks: clicks[1] }
+                PressureCorner { measured: measured.rear_left, clicks: clicks[2] }
+                PressureCorner { measured: measured.rear_right, clicks: clicks[3] }
+            }
+            div { class: "label px-0 py-2",
+                span { class: "label-text text-nowrap px-4", "Measured" }
+                span { class: "label-text text-nowrap px-4", "{correction.laps} Laps" }
+            }
+            ul { class: "menu menu-horizontal rounded-box gap-2 w-max pt-0",
+                li {
+                    button {
+                        class: "btn btn-sm",
+                        disabled: correction.is_empty(),
+                        onclick: move |_| router.do_send(SetupChange::ApplyPressureCorrection(false, String::new())),
+                        "Apply"
+                    }
+                }
+                li {
+                    button {
+                        class: "btn btn-sm",
+                        disabled: correction.is_empty(),
+                        onclick: move |_| {
+                            let author = settings.read().author.clone();
+                            template_router.do_send(SetupChange::ApplyPressureCorrection(true, author));
+                        },
+                        "Apply to Templates"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn PressureCorner(measured: f32, clicks: i32) -> Element {
+    let color = if clicks > 0 {
+        "text-green"
+    } else if clicks < 0 {
+        "text-red"
+    } else {
+        ""
+    };
+
+    rsx! {
+        div { class: "grid grid-cols-2 gap-2 bg-surface0 rounded-md px-2 py-1",
+            span { "{measured:.1}" }
+            span { class: "justify-self-end {color}", "{clicks:+}" }
+        }
+    }
+}
@@ -7,9 +7,12 @@ use thiserror::Error;
 use tracing::{debug, error};
 
 use crate::{
-    actors::{fuel_calculator::FuelMessage, setup_manager::SetupChange},
+    actors::{
+        fuel_calculator::FuelMessage,
+        motec::{MotecMessage, MotecRetention},
+        setup_manager::{SetupChange, TemperatureGrid},
+    },
     ui::components::{InputNumber, ThemeSwitcher},
-    PROGRAM_NAME,
 };
 
 use super::theme::Theme;
@@ -19,6 +22,19 @@ pub struct Settings {
     pub telemetry_laps: i32,
     pub reserve_laps: i32,
     pub theme: Theme,
+    /// Name saved with every template version
+    #[serde(default)]
+    pub author: String,
+    /// Temperatures to write adjusted setups for ahead of a session
+    #[serde(default)]
+    pub temperature_grid: TemperatureGrid,
+    #[serde(default)]
+    pub temperature_grid_enabled: bool,
+    /// Move the MoTeC files ACC writes into an archive after every session
+    #[serde(default)]
+    pub motec_archive: bool,
+    #[serde(default)]
+    pub motec_retention: MotecRetention,
 }
 
 impl Drop for Settings {
@@ -37,12 +53,7 @@ pub enum SettingsError {
 
 impl Settings {
     pub fn path() -> Result<PathBuf, SettingsError> {
-        #[cfg(windows)]
-        let mut documents =
-            known_folders::get_known_folder_path(known_folders::KnownFolder::Documents).unwrap();
-        #[cfg(not(windows))]
-        let mut documents = PathBuf::from("./setups");
-        documents.push(PROGRAM_NAME);
+        let mut documents = crate::data_folder();
         if !documents.exists() {
             std::fs::create_dir_all(&documents)?
         }
@@ -65,6 +76,16 @@ impl Settings {
         Ok(())
     }
 
+    /// Temperature grid the setup manager writes setups for, if turned on
+    pub fn temperature_grid(&self) -> Option<TemperatureGrid> {
+        self.temperature_grid_enabled.then_some(self.temperature_grid)
+    }
+
+    /// Retention of the MoTeC archive, if archiving is turned on
+    pub fn motec_retention(&self) -> Option<MotecRetention> {
+        self.motec_archive.then_some(self.motec_retention)
+    }
+
     pub fn init() -> Settings {
         let settings = match Settings::load() {
             Ok(settings) => settings,
@@ -98,16 +119,66 @@ pub fn SettingsComponent() -> Element {
         settings.write().reserve_laps = reserve_laps();
     });
 
+    let grid = settings.read().temperature_grid;
+    let grid_enabled = use_signal(|| settings.read().temperature_grid_enabled);
+    let ambient_from = use_signal(|| grid.ambient_from);
+    let ambient_to = use_signal(|| grid.ambient_to);
+    let track_from = use_signal(|| grid.track_from);
+    let track_to = use_signal(|| grid.track_to);
+    let step = use_signal(|| grid.step);
+    use_effect(move || {
+        let router: Addr<crate::actors::Router> = use_context();
+        let grid = TemperatureGrid {
+            ambient_from: ambient_from(),
+            ambient_to: ambient_to(),
+            track_from: track_from(),
+            track_to: track_to(),
+            step: step(),
+        };
+        debug!("changed temperature grid: {:?}", grid);
+        let mut settings = settings.write();
+        settings.temperature_grid = grid;
+        settings.temperature_grid_enabled = grid_enabled();
+        router.do_send(SetupChange::TemperatureGrid(settings.temperature_grid()));
+    });
+
+    let retention = settings.read().motec_retention;
+    let motec_archive = use_signal(|| settings.read().motec_archive);
+    let retention_days = use_signal(|| retention.days.unwrap_or_default());
+    let retention_sessions = use_signal(|| retention.sessions.unwrap_or_default());
+    use_effect(move || {
+        let router: Addr<crate::actors::Router> = use_context();
+        // No limit is kept as 0 in the inputs
+        let retention = MotecRetention {
+            days: Some(retention_days()).filter(|days| *days > 0),
+            sessions: Some(retention_sessions()).filter(|sessions| *sessions > 0),
+        };
+        debug!("changed motec retention: {:?}", retention);
+        let mut settings = settings.write();
+        settings.motec_retention = retention;
+        settings.motec_archive = motec_archive();
+        router.do_send(MotecMessage::Retention(settings.motec_retention()));
+    });
+
     static VERSION: &str = env!("CARGO_PKG_VERSION");
 
     rsx! {
-        div { class: "grid grid-rows-[min-content,min-content,1fr,min-content] bg-base rounded-md shadow-lg p-4 gap-4",
+        div { class: "grid grid-rows-[min-content,min-content,min-content,1fr,min-content] bg-base rounded-md shadow-lg p-4 gap-4",
             div { class: "grid gap-2",
                 h1 { class: "text-xl", "App" }
                 div { class: "label bg-surface0 rounded-md h-min px-2 pr-4",
                     span { class: "text-lg pl-8 label-text text-nowrap", "Theme" }
                     ThemeSwitcher { }
                 }
+                div { class: "label bg-surface0 rounded-md h-min px-2 pr-4",
+                    span { class: "text-lg pl-8 label-text text-nowrap", "Author" }
+                    input {
+                        class: "input input-bordered",
+                        r#type: "text",
+                        value: "{settings.read().author}",
+                        oninput: move |event| settings.write().author = event.value(),
+                    }
+                }
             }
             div { class: "grid gap-2",
                 h1 { class: "text-xl", "Setups" }
@@ -125,6 +196,22 @@ pub fn SettingsComponent() -> Element {
                     max: 99,
                     step: 1
                 }
+                TemperatureGridSettings {
+                    enabled: grid_enabled,
+                    ambient_from,
+                    ambient_to,
+                    track_from,
+                    track_to,
+                    step,
+                }
+            }
+            div { class: "grid gap-2",
+                h1 { class: "text-xl", "Telemetry" }
+                MotecSettings {
+                    enabled: motec_archive,
+                    days: retention_days,
+                    sessions: retention_sessions,
+                }
             }
             div { class: "grid gap-2 self-center" }
             div { class: "grid gap-2 justify-self-center",
@@ -133,3 +220,67 @@ pub fn SettingsComponent() -> Element {
         }
     }
 }
+
+/// Range of temperatures to write adjusted setups for besides the live weather
+#[component]
+fn TemperatureGridSettings(
+    enabled: Signal<bool>,
+    ambient_from: Signal<u8>,
+    ambient_to: Signal<u8>,
+    track_from: Signal<u8>,
+    track_to: Signal<u8>,
+    step: Signal<u8>,
+) -> Element {
+    let grid = TemperatureGrid {
+        ambient_from: ambient_from(),
+        ambient_to: ambient_to(),
+        track_from: track_from(),
+        track_to: track_to(),
+        step: step(),
+    };
+    let points = grid.points().len();
+
+    rsx! {
+        div { class: "label bg-surface0 rounded-md h-min px-2 pr-4",
+            span { class: "text-lg pl-8 label-text text-nowrap", "Temperature Grid" }
+            div { class: "label gap-4 p-0",
+                if enabled() {
+                    span { class: "text-sm", "{points} setups per template" }
+                }
+                input {
+                    r#type: "checkbox",
+                    class: "toggle",
+                    checked: enabled(),
+                    onchange: move |event| enabled.set(event.checked()),
+                }
+            }
+        }
+        if enabled() {
+            InputNumber::<u8> { name: "Ambient From", value: ambient_from, min: 0, max: 50, step: 1 }
+            InputNumber::<u8> { name: "Ambient To", value: ambient_to, min: 0, max: 50, step: 1 }
+            InputNumber::<u8> { name: "Track From", value: track_from, min: 0, max: 60, step: 1 }
+            InputNumber::<u8> { name: "Track To", value: track_to, min: 0, max: 60, step: 1 }
+            InputNumber::<u8> { name: "Step", value: step, min: 1, max: 10, step: 1 }
+        }
+    }
+}
+
+/// Archive of the MoTeC files and how long to keep them, 0 keeps them forever
+#[component]
+fn MotecSettings(enabled: Signal<bool>, days: Signal<u32>, sessions: Signal<u32>) -> Element {
+    rsx! {
+        div { class: "label bg-surface0 rounded-md h-min px-2 pr-4",
+            span { class: "text-lg pl-8 label-text text-nowrap", "Archive MoTeC Files" }
+            input {
+                r#type: "checkbox",
+                class: "toggle",
+                checked: enabled(),
+                onchange: move |event| enabled.set(event.checked()),
+            }
+        }
+        if enabled() {
+            InputNumber::<u32> { name: "Keep Days", value: days, min: 0, max: 3650, step: 1 }
+            InputNumber::<u32> { name: "Keep Sessions per Track", value: sessions, min: 0, max: 999, step: 1 }
+        }
+    }
+}
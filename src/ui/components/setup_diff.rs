@@ -0,0 +1,118 @@
+use dioxus::prelude::*;
+
+use crate::actors::{
+    setup_manager::{diff, FieldChange, SetupFile},
+    ui::Setups,
+};
+
+/// Prefixes of the selection values telling templates and adjusted setups apart
+const TEMPLATE: &str = "template:";
+const ADJUSTED: &str = "adjusted:";
+
+#[component]
+pub fn SetupDiffView() -> Element {
+    let setups: SyncSignal<Setups> = use_context();
+    let mut from = use_signal(String::new);
+    let mut to = use_signal(String::new);
+
+    let setups = setups.read();
+    if setups.templates.is_empty() {
+        return rsx! {};
+    }
+
+    // Default to comparing the first template with its adjusted copy
+    let first = setups.templates.keys().next().cloned().unwrap_or_default();
+    let from_key = if from().is_empty() {
+        format!("{TEMPLATE}{first}")
+    } else {
+        from()
+    };
+    let to_key = if to().is_empty() {
+        format!("{ADJUSTED}{first}")
+    } else {
+        to()
+    };
+
+    let changes = match (select(&setups, &from_key), select(&setups, &to_key)) {
+        (Some(from), Some(to)) => diff(&from.setup, &to.setup),
+        _ => Vec::new(),
+    };
+
+    let options = options(&setups);
+
+    rsx! {
+        div { class: "grid grid-rows-[min-content_min-content_auto] p-2 bg-base rounded-lg shadow-lg",
+            div { class: "grid grid-cols-1",
+                h1 { class: "text-xl pb-2 justify-self-center", "Compare" }
+            }
+            div { class: "grid grid-cols-2 gap-4 pb-2",
+                select {
+                    class: "select select-bordered select-sm",
+                    oninput: move |event| from.set(event.value()),
+                    { options.iter().map(|(key, name)| rsx! {
+                        option { class: "p-2", value: "{key}", selected: *key == from_key, "{name}" }
+                    })}
+                }
+                select {
+                    class: "select select-bordered select-sm",
+                    oninput: move |event| to.set(event.value()),
+                    { options.iter().map(|(key, name)| rsx! {
+                        option { class: "p-2", value: "{key}", selected: *key == to_key, "{name}" }
+                    })}
+                }
+            }
+            div { class: "grid auto-rows-min gap-1 overflow-y-auto",
+                if changes.is_empty() {
+                    div { class: "divider divider-vertical mx-2", "No Differences" }
+                }
+                { changes.into_iter().map(|change| rsx! { FieldChangeRow { change } }) }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn FieldChangeRow(change: FieldChange) -> Element {
+    let color = match change.clicks() {
+        Some(clicks) if clicks > 0 => "text-green",
+        Some(clicks) if clicks < 0 => "text-red",
+        _ => "",
+    };
+    let clicks = change.clicks().map(|c| format!("{c:+}")).unwrap_or_default();
+
+    rsx! {
+        div {
+            class: "grid grid-cols-[1fr_1fr_1fr_1fr_min-content] gap-2 bg-surface0 rounded-md px-2 py-1 text-sm",
+            title: "{change.path}",
+            span { class: "text-nowrap", "{change.section}" }
+            span { class: "text-nowrap", "{change.label}" }
+            span { class: "text-nowrap", "{change.format(&change.from)}" }
+            span { class: "text-nowrap", "{change.format(&change.to)}" }
+            span { class: "justify-self-end {color}", "{clicks}" }
+        }
+    }
+}
+
+/// Selection values and names of every template and adjusted setup
+pub fn options(setups: &Setups) -> Vec<(String, String)> {
+    setups
+        .templates
+        .keys()
+        .map(|name| (format!("{TEMPLATE}{name}"), format!("Template {name}")))
+        .chain(
+            setups
+                .adjusted
+                .keys()
+                .map(|name| (format!("{ADJUSTED}{name}"), format!("Adjusted {name}"))),
+        )
+        .collect()
+}
+
+/// Template or adjusted setup of a selection value
+pub fn select<'a>(setups: &'a Setups, key: &str) -> Option<&'a SetupFile> {
+    if let Some(name) = key.strip_prefix(TEMPLATE) {
+        setups.templates.get(name)
+    } else {
+        setups.adjusted.get(key.strip_prefix(ADJUSTED)?)
+    }
+}
@@ -0,0 +1,305 @@
+use actix::prelude::*;
+use dioxus::prelude::*;
+use serde_json::Value;
+use strum::IntoEnumIterator;
+use tracing::error;
+
+use crate::{
+    actors::{
+        setup_manager::{
+            fields, set_field, validate, Setup, SetupChange, SetupCondition, SetupField, SetupInfo,
+            SetupType, VersionInfo,
+        },
+        ui::Setups,
+    },
+    ui::{components::Settings, Route},
+};
+
+#[component]
+pub fn SetupEditor(name: String) -> Element {
+    let setups: SyncSignal<Setups> = use_context();
+    let router: Addr<crate::actors::Router> = use_context();
+    let settings: Signal<Settings> = use_context();
+
+    let template = setups
+        .read()
+        .templates
+        .get(&name)
+        .map(|template| template.setup.clone());
+    let base = setups
+        .read()
+        .templates
+        .get(&name)
+        .and_then(|template| template.patch.as_ref())
+        .map(|patch| patch.base.clone());
+    let mut setup: Signal<Option<Setup>> = use_signal(|| template.clone());
+    let mut note = use_signal(String::new);
+
+    // The editor is kept when another template is opened, only its name changes
+    use_effect(use_reactive!(|name| {
+        setup.set(
+            setups
+                .peek()
+                .templates
+                .get(&name)
+                .map(|template| template.setup.clone()),
+        );
+        note.set(String::new());
+    }));
+
+    let Some(edited) = setup() else {
+        return rsx! {
+            div { class: "grid p-2 bg-base rounded-lg shadow-lg",
+                div { class: "divider divider-vertical h-full mx-2 justify-self-center",
+                    "No Setup Loaded"
+                }
+            }
+        };
+    };
+
+    let warnings = validate(&edited);
+    let changed = template.as_ref() != Some(&edited);
+
+    // Group the fields by their section, keeping the order of the setup
+    let mut sections: Vec<(String, Vec<SetupField>)> = Vec::new();
+    for field in fields(&edited) {
+        match sections.last_mut() {
+            Some((section, fields)) if *section == field.section => fields.push(field),
+            _ => sections.push((field.section.clone(), vec![field])),
+        }
+    }
+
+    let save_name = name.clone();
+
+    rsx! {
+        div { class: "grid grid-rows-[min-content_min-content_min-content_auto] p-2 bg-base rounded-lg shadow-lg overflow-hidden",
+            div { class: "grid grid-cols-[1fr_max-content] pb-2",
+                div { class: "grid auto-rows-min",
+                    h1 { class: "text-xl", "{name}" }
+                    if let Some(base) = base {
+                        span { class: "text-sm", "Based on {base}, only the changes to it are saved" }
+                    }
+                }
+                ul { class: "menu menu-horizontal rounded-box gap-2 p-0",
+                    li {
+                        button {
+                            class: "btn btn-sm",
+                            onclick: move |_| {
+                                if let Some(setup) = setup.write().as_mut() {
+                                    let stops = &mut setup.basic_setup.strategy.pit_strategy;
+                                    stops.push(stops.last().cloned().unwrap_or_default());
+                                }
+                            },
+                            "Add Pit Stop"
+                        }
+                    }
+                    li {
+                        button {
+                            class: "btn btn-sm",
+                            disabled: edited.basic_setup.strategy.pit_strategy.is_empty(),
+                            onclick: move |_| {
+                                if let Some(setup) = setup.write().as_mut() {
+                                    setup.basic_setup.strategy.pit_strategy.pop();
+                                }
+                            },
+                            "Remove Pit Stop"
+                        }
+                    }
+                    li {
+                        button {
+                            class: "btn btn-sm",
+                            disabled: !changed,
+                            onclick: move |_| setup.set(template.clone()),
+                            "Reset"
+                        }
+                    }
+                    li {
+                        button {
+                            class: "btn btn-sm",
+                            disabled: !changed,
+                            onclick: move |_| {
+                                if let Some(setup) = setup() {
+                                    let info = VersionInfo {
+                                        author: settings.read().author.clone(),
+                                        note: note(),
+                                    };
+                                    router.do_send(SetupChange::SaveTemplate(save_name.clone(), Box::new(setup), info));
+                                    note.set(String::new());
+                                }
+                            },
+                            "Save"
+                        }
+                    }
+                    li {
+                        Link { class: "btn btn-sm", to: Route::Setups {}, "Close" }
+                    }
+                }
+            }
+            TemplateInfoForm { name: name.clone() }
+            div { class: "grid auto-rows-min gap-1 pb-2",
+                input {
+                    class: "input input-sm input-bordered",
+                    r#type: "text",
+                    placeholder: "Note for this version",
+                    value: "{note}",
+                    oninput: move |event| note.set(event.value()),
+                }
+                { warnings.iter().filter_map(SetupField::warning).map(|warning| rsx! {
+                    span { class: "text-warning text-sm", "{warning}" }
+                })}
+            }
+            div { class: "grid grid-cols-2 auto-rows-min gap-4 overflow-y-auto",
+                { sections.into_iter().map(|(section, fields)| rsx! {
+                    div { class: "grid auto-rows-min gap-1",
+                        h1 { class: "text-md pb-1", "{section}" }
+                        { fields.into_iter().map(|field| rsx! { FieldInput { field, setup } }) }
+                    }
+                })}
+            }
+        }
+    }
+}
+
+#[component]
+fn FieldInput(field: SetupField, setup: Signal<Option<Setup>>) -> Element {
+    let clicks = field.value.as_i64();
+    let physical = match (clicks, &field.parameter) {
+        (Some(clicks), Some(parameter)) => parameter.format(clicks as i32),
+        _ => String::new(),
+    };
+    let (min, max) = field
+        .parameter
+        .map(|parameter| (parameter.min.to_string(), parameter.max.to_string()))
+        .unwrap_or_default();
+    let warning = field.warning().is_some();
+    let path = field.path.clone();
+
+    rsx! {
+        div { class: "grid grid-cols-[1fr_min-content_5rem] items-center gap-2 bg-surface0 rounded-md px-2 py-1 text-sm",
+            span { class: "text-nowrap", title: "{field.path}", "{field.label}" }
+            input {
+                r#type: "number",
+                class: "input input-xs input-bordered w-20",
+                class: if warning { "input-warning text-warning" },
+                min: "{min}",
+                max: "{max}",
+                step: if clicks.is_some() { "1" } else { "0.01" },
+                value: "{field.value}",
+                oninput: move |event| {
+                    let value = if clicks.is_some() {
+                        event.value().parse::<i64>().ok().map(Value::from)
+                    } else {
+                        event.value().parse::<f64>().ok().map(Value::from)
+                    };
+                    let Some(value) = value else {
+                        return;
+                    };
+
+                    if let Some(setup) = setup.write().as_mut() {
+                        if let Err(e) = set_field(setup, &path, value) {
+                            error!("failed to set setup field {path}: {e}");
+                        }
+                    }
+                }
+            }
+            span { class: "justify-self-end text-nowrap", "{physical}" }
+        }
+    }
+}
+
+/// Reference temperatures, type, condition and notes of a template, saved to its sidecar
+#[component]
+fn TemplateInfoForm(name: String) -> Element {
+    let setups: SyncSignal<Setups> = use_context();
+    let router: Addr<crate::actors::Router> = use_context();
+    let settings: Signal<Settings> = use_context();
+
+    let template = setups.read().templates.get(&name).map(|template| template.info());
+    let mut info: Signal<Option<SetupInfo>> = use_signal(|| template.clone());
+
+    use_effect(use_reactive!(|name| {
+        info.set(setups.peek().templates.get(&name).map(|template| template.info()));
+    }));
+
+    let Some(edited) = info() else {
+        return rsx! {};
+    };
+    let changed = template.as_ref() != Some(&edited);
+
+    rsx! {
+        div { class: "grid grid-cols-[repeat(4,_max-content)_1fr_max-content] items-center gap-2 pb-2 text-sm",
+            label { class: "grid grid-cols-[max-content_4rem] items-center gap-2",
+                span { "Ambient °C" }
+                input {
+                    r#type: "number",
+                    class: "input input-xs input-bordered",
+                    value: "{edited.ambient_temperature}",
+                    oninput: move |event| {
+                        if let (Ok(value), Some(info)) = (event.value().parse::<u8>(), info.write().as_mut()) {
+                            info.ambient_temperature = value;
+                        }
+                    }
+                }
+            }
+            label { class: "grid grid-cols-[max-content_4rem] items-center gap-2",
+                span { "Track °C" }
+                input {
+                    r#type: "number",
+                    class: "input input-xs input-bordered",
+                    value: "{edited.track_temperature}",
+                    oninput: move |event| {
+                        if let (Ok(value), Some(info)) = (event.value().parse::<u8>(), info.write().as_mut()) {
+                            info.track_temperature = value;
+                        }
+                    }
+                }
+            }
+            select {
+                class: "select select-bordered select-xs",
+                oninput: move |event| {
+                    let setup_type = SetupType::iter().find(|t| t.to_string() == event.value());
+                    if let (Some(setup_type), Some(info)) = (setup_type, info.write().as_mut()) {
+                        info.setup_type = setup_type;
+                    }
+                },
+                { SetupType::iter().map(|t| rsx! {
+                    option { selected: t == edited.setup_type, "{t}" }
+                })}
+            }
+            select {
+                class: "select select-bordered select-xs",
+                oninput: move |event| {
+                    let condition = SetupCondition::iter().find(|c| c.to_string() == event.value());
+                    if let (Some(condition), Some(info)) = (condition, info.write().as_mut()) {
+                        info.condition = condition;
+                    }
+                },
+                { SetupCondition::iter().map(|c| rsx! {
+                    option { selected: c == edited.condition, "{c}" }
+                })}
+            }
+            input {
+                class: "input input-xs input-bordered",
+                r#type: "text",
+                placeholder: "Notes",
+                value: "{edited.notes}",
+                oninput: move |event| {
+                    if let Some(info) = info.write().as_mut() {
+                        info.notes = event.value();
+                    }
+                }
+            }
+            button {
+                class: "btn btn-xs",
+                disabled: !changed,
+                onclick: move |_| {
+                    if let Some(mut info) = info() {
+                        info.author = settings.read().author.clone();
+                        router.do_send(SetupChange::SaveTemplateInfo(name.clone(), info));
+                    }
+                },
+                "Save Info"
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use actix::prelude::*;
+use dioxus::prelude::*;
+
+use crate::{
+    actors::{
+        setup_manager::{diff, SetupChange, SetupVersion, VersionInfo},
+        ui::Setups,
+    },
+    ui::components::{setup_diff::FieldChangeRow, Settings},
+};
+
+#[component]
+pub fn SetupHistoryView() -> Element {
+    let setups: SyncSignal<Setups> = use_context();
+    let mut selected = use_signal(String::new);
+
+    let setups = setups.read();
+    if setups.templates.is_empty() {
+        return rsx! {};
+    }
+
+    let name = if selected().is_empty() {
+        setups.templates.keys().next().cloned().unwrap_or_default()
+    } else {
+        selected()
+    };
+    let versions = setups.history.get(&name).cloned().unwrap_or_default();
+
+    rsx! {
+        div { class: "grid grid-rows-[min-content_min-content_auto] p-2 bg-base rounded-lg shadow-lg",
+            div { class: "grid grid-cols-1",
+                h1 { class: "text-xl pb-2 justify-self-center", "History" }
+            }
+            select {
+                class: "select select-bordered select-sm mb-2",
+                oninput: move |event| selected.set(event.value()),
+                { setups.templates.keys().map(|template| rsx! {
+                    option { class: "p-2", selected: *template == name, "{template}" }
+                })}
+            }
+            div { class: "grid auto-rows-min gap-2 overflow-y-auto",
+                if versions.is_empty() {
+                    div { class: "divider divider-vertical mx-2", "No Versions" }
+                }
+                // Newest first, the newest being the current template
+                { versions.iter().rev().enumerate().map(|(i, version)| rsx! {
+                    VersionRow {
+                        name: name.clone(),
+                        version: version.clone(),
+                        current: versions.last().cloned(),
+                        latest: i == 0
+                    }
+                })}
+            }
+        }
+    }
+}
+
+#[component]
+fn VersionRow(name: String, version: SetupVersion, current: Option<SetupVersion>, latest: bool) -> Element {
+    let router: Addr<crate::actors::Router> = use_context();
+    let settings: Signal<Settings> = use_context();
+    let mut expanded = use_signal(|| false);
+
+    let timestamp = version.timestamp.format("%Y-%m-%d %H:%M").to_string();
+    let restore_note = format!("Restored version from {timestamp}");
+    let changes = match (&current, expanded()) {
+        (Some(current), true) => diff(&version.setup, &current.setup),
+        _ => Vec::new(),
+    };
+    let restore_timestamp = version.timestamp;
+
+    rsx! {
+        div { class: "grid gap-1 bg-surface0 rounded-md p-2",
+            div { class: "grid grid-cols-[max-content_1fr_max-content] gap-2 items-center",
+                span { class: "text-nowrap", "{timestamp}" }
+                span { class: "text-nowrap text-sm", "{version.info.author}" }
+                ul { class: "menu menu-horizontal rounded-box gap-2 p-0",
+                    li {
+                        button {
+                            class: "btn btn-xs",
+                            disabled: latest,
+                            onclick: move |_| expanded.toggle(),
+                            "Compare"
+                        }
+                    }
+                    li {
+                        button {
+                            class: "btn btn-xs",
+                            disabled: latest,
+                            onclick: move |_| {
+                                let info = VersionInfo {
+                                    author: settings.read().author.clone(),
+                                    note: restore_note.clone(),
+                                };
+                                router.do_send(SetupChange::RestoreVersion(name.clone(), restore_timestamp, info));
+                            },
+                            "Restore"
+                        }
+                    }
+                }
+            }
+            if !version.info.note.is_empty() {
+                span { class: "text-sm", "{version.info.note}" }
+            }
+            { changes.into_iter().map(|change| rsx! { FieldChangeRow { change } }) }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use actix::prelude::*;
+use dioxus::prelude::*;
+use strum::IntoEnumIterator;
+
+use crate::{
+    actors::{
+        setup_manager::{SetupChange, SheetFormat},
+        ui::Setups,
+    },
+    ui::components::setup_diff::{options, select},
+};
+
+#[component]
+pub fn SetupSheetView() -> Element {
+    let setups: SyncSignal<Setups> = use_context();
+    let router: Addr<crate::actors::Router> = use_context();
+    let mut setup = use_signal(String::new);
+    let mut compare = use_signal(String::new);
+    let mut format = use_signal(SheetFormat::default);
+
+    let setups = setups.read();
+    let options = options(&setups);
+    let Some((first, _)) = options.first() else {
+        return rsx! {};
+    };
+    let setup_key = if setup().is_empty() {
+        first.clone()
+    } else {
+        setup()
+    };
+
+    let file = select(&setups, &setup_key).cloned();
+    let compare_file = select(&setups, &compare()).cloned();
+    let sheet = setups.sheet.as_ref().map(|path| path.display().to_string());
+
+    rsx! {
+        div { class: "grid auto-rows-min bg-base rounded-lg shadow-lg",
+            div { class: "label px-0 py-2 border-b-[1px] border-crust",
+                span { class: "label-text text-nowrap px-4", "Setup Sheet" }
+            }
+            div { class: "grid gap-2 p-4",
+                select {
+                    class: "select select-bordered select-sm",
+                    oninput: move |event| setup.set(event.value()),
+                    { options.iter().map(|(key, name)| rsx! {
+                        option { value: "{key}", selected: *key == setup_key, "{name}" }
+                    })}
+                }
+                select {
+                    class: "select select-bordered select-sm",
+                    oninput: move |event| compare.set(event.value()),
+                    option { value: "", selected: compare().is_empty(), "No Comparison" }
+                    { options.iter().map(|(key, name)| rsx! {
+                        option { value: "{key}", selected: *key == compare(), "Compare to {name}" }
+                    })}
+                }
+                select {
+                    class: "select select-bordered select-sm",
+                    oninput: move |event| {
+                        if let Some(selected) = SheetFormat::iter().find(|f| f.to_string() == event.value()) {
+                            format.set(selected);
+                        }
+                    },
+                    { SheetFormat::iter().map(|f| rsx! {
+                        option { value: "{f}", selected: f == format(), "{f}" }
+                    })}
+                }
+                button {
+                    class: "btn btn-sm",
+                    disabled: file.is_none(),
+                    onclick: move |_| {
+                        if let Some(file) = file.clone() {
+                            let compare = compare_file.clone().map(Box::new);
+                            router.do_send(SetupChange::ExportSheet(Box::new(file), compare, format()));
+                        }
+                    },
+                    "Export"
+                }
+                if let Some(sheet) = sheet {
+                    span { class: "text-sm break-all", title: "{sheet}", "Saved to {sheet}" }
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,12 @@
 use dioxus::prelude::*;
 
-use crate::actors::{setup_manager::SetupType, ui::Setups};
+use crate::{
+    actors::{
+        setup_manager::{SetupCondition, SetupFile, SetupType},
+        ui::Setups,
+    },
+    ui::Route,
+};
 
 #[component]
 pub fn SetupView() -> Element {
@@ -17,13 +23,19 @@ pub fn SetupView() -> Element {
                     div {
                         h1 { class: "text-md pb-2", "Templates" }
                         div { class: "grid auto-rows-min gap-2",
-                            { setups.templates.iter().map(|(_name, setup)| { rsx! {
-                                SetupSmall {
-                                    name: "{setup.name}",
-                                    setup_type: setup.setup_type,
-                                    ambient_temp: setup.ambient_temperature,
-                                    track_temp: setup.track_temperature,
-                                    fuel: setup.setup.basic_setup.strategy.fuel
+                            { setups.templates.iter().map(|(name, setup)| { rsx! {
+                                Link {
+                                    to: Route::SetupEditor { name: name.clone() },
+                                    SetupSmall {
+                                        name: "{setup.name}",
+                                        setup_type: setup.setup_type,
+                                        condition: setup.condition,
+                                        recommended: setup.condition == setups.condition,
+                                        ambient_temp: setup.ambient_temperature,
+                                        track_temp: setup.track_temperature,
+                                        fuel: setup.setup.basic_setup.strategy.fuel,
+                                        pressures: tyre_pressures(setup)
+                                    }
                                 }
                             }})}
                         }
@@ -38,9 +50,12 @@ pub fn SetupView() -> Element {
                                     SetupSmall {
                                         name: "{setup.name}",
                                         setup_type: setup.setup_type,
+                                        condition: setup.condition,
+                                        recommended: setup.condition == setups.condition,
                                         ambient_temp: setup.ambient_temperature,
                                         track_temp: setup.track_temperature,
-                                        fuel: setup.setup.basic_setup.strategy.fuel
+                                        fuel: setup.setup.basic_setup.strategy.fuel,
+                                        pressures: tyre_pressures(setup)
                                     }
                                 }})
                             }}
@@ -62,12 +77,17 @@ pub fn SetupView() -> Element {
 pub fn SetupSmall(
     name: String,
     setup_type: SetupType,
+    condition: SetupCondition,
+    recommended: bool,
     ambient_temp: u8,
     track_temp: u8,
     fuel: i32,
+    pressures: Vec<String>,
 ) -> Element {
     rsx! {
-        div { class: "grid grid-rows-[min-content_min-content] bg-surface0 rounded-md p-2",
+        div {
+            class: "grid grid-rows-[min-content_min-content_min-content] bg-surface0 rounded-md p-2",
+            class: if recommended { "outline outline-2 outline-green" },
             div {
                 "{name}"
                 { match setup_type {
@@ -87,6 +107,19 @@ pub fn SetupSmall(
                         }
                     },
                 }}
+                { match condition {
+                    SetupCondition::Dry => rsx! {},
+                    SetupCondition::Intermediate => rsx! {
+                        div { class: "ml-2 badge bg-teal text-black",
+                            "I"
+                        }
+                    },
+                    SetupCondition::Wet => rsx! {
+                        div { class: "ml-2 badge bg-blue text-black",
+                            "W"
+                        }
+                    },
+                }}
             }
             div {
                 div { class: "badge bg-sky text-black",
@@ -123,6 +156,22 @@ pub fn SetupSmall(
                     "{fuel} l"
                 }
             }
+            div { class: "grid grid-cols-[min-content_min-content] gap-x-2 pt-2 text-xs",
+                { pressures.iter().map(|pressure| rsx! { div { class: "text-nowrap", "{pressure}" } }) }
+            }
         }
     }
 }
+
+/// Cold tyre pressures of the setup in psi, in FL, FR, RL, RR order
+fn tyre_pressures(setup: &SetupFile) -> Vec<String> {
+    let pressure = &setup.parameters().tyre_pressure;
+    setup
+        .setup
+        .basic_setup
+        .tyres
+        .tyre_pressure
+        .iter()
+        .map(|clicks| pressure.format(*clicks))
+        .collect()
+}
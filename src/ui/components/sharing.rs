@@ -0,0 +1,202 @@
+use std::path::PathBuf;
+
+use actix::prelude::*;
+use dioxus::prelude::*;
+
+use crate::{
+    actors::{
+        setup_manager::{BundleConflict, BundlePreview, CarTrack, SetupChange},
+        ui::Setups,
+    },
+    ui::components::{setup_diff::FieldChangeRow, Settings},
+};
+
+#[component]
+pub fn Sharing() -> Element {
+    let router: Addr<crate::actors::Router> = use_context();
+    use_hook(|| router.do_send(SetupChange::RefreshLibrary));
+
+    rsx! {
+        div { class: "grid grid-cols-2 gap-2 min-h-0",
+            ExportView {}
+            ImportView {}
+        }
+    }
+}
+
+#[component]
+fn ExportView() -> Element {
+    let setups: SyncSignal<Setups> = use_context();
+    let router: Addr<crate::actors::Router> = use_context();
+    let settings: Signal<Settings> = use_context();
+    let mut selection: Signal<Vec<CarTrack>> = use_signal(Vec::new);
+
+    let tracks = setups.read().library.tracks.clone();
+
+    rsx! {
+        div { class: "grid grid-rows-[min-content_auto_min-content] p-2 bg-base rounded-lg shadow-lg min-h-0",
+            div { class: "grid grid-cols-1",
+                h1 { class: "text-xl pb-2 justify-self-center", "Export" }
+            }
+            div { class: "grid auto-rows-min gap-2 overflow-y-auto",
+                if tracks.is_empty() {
+                    div { class: "divider divider-vertical mx-2", "No Templates" }
+                }
+                { tracks.into_iter().map(|track| {
+                    let checked = selection.read().contains(&track);
+                    let label = format!("{} - {}", track.car, track.track);
+                    rsx! {
+                        label { class: "grid grid-cols-[min-content_1fr] gap-2 items-center bg-surface0 rounded-md p-2 cursor-pointer",
+                            input {
+                                r#type: "checkbox",
+                                class: "checkbox checkbox-sm",
+                                checked,
+                                onchange: move |_| {
+                                    let mut selection = selection.write();
+                                    match selection.iter().position(|t| *t == track) {
+                                        Some(index) => {
+                                            selection.remove(index);
+                                        }
+                                        None => selection.push(track.clone()),
+                                    }
+                                }
+                            }
+                            span { "{label}" }
+                        }
+                    }
+                })}
+            }
+            button {
+                class: "btn btn-sm mt-2",
+                disabled: selection.read().is_empty(),
+                onclick: move |_| {
+                    let author = settings.read().author.clone();
+                    router.do_send(SetupChange::ExportBundle(selection(), author));
+                    selection.set(Vec::new());
+                },
+                "Export Bundle"
+            }
+        }
+    }
+}
+
+#[component]
+fn ImportView() -> Element {
+    let setups: SyncSignal<Setups> = use_context();
+    let router: Addr<crate::actors::Router> = use_context();
+
+    let setups = setups.read();
+    let bundles = setups.library.bundles.clone();
+    let selected = setups.bundle.as_ref().map(|bundle| bundle.path.clone());
+    let folder = setups.library.folder.display().to_string();
+
+    rsx! {
+        div { class: "grid grid-rows-[min-content_min-content_min-content_auto] p-2 bg-base rounded-lg shadow-lg min-h-0",
+            div { class: "grid grid-cols-1",
+                h1 { class: "text-xl pb-2 justify-self-center", "Import" }
+            }
+            span { class: "text-sm pb-2", "Bundles are read from {folder}" }
+            select {
+                class: "select select-bordered select-sm mb-2",
+                oninput: move |event| {
+                    let path = Some(event.value()).filter(|path| !path.is_empty()).map(PathBuf::from);
+                    router.do_send(SetupChange::PreviewBundle(path));
+                },
+                option { value: "", selected: selected.is_none(), "Select a bundle" }
+                { bundles.iter().map(|path| {
+                    let name = path.file_stem().map(|name| name.to_string_lossy()).unwrap_or_default();
+                    rsx! {
+                        option {
+                            value: "{path.display()}",
+                            selected: selected.as_ref() == Some(path),
+                            "{name}"
+                        }
+                    }
+                })}
+            }
+            if let Some(bundle) = setups.bundle.clone() {
+                BundlePreviewView { bundle }
+            }
+        }
+    }
+}
+
+#[component]
+fn BundlePreviewView(bundle: BundlePreview) -> Element {
+    let router: Addr<crate::actors::Router> = use_context();
+
+    let created = bundle.manifest.created.format("%Y-%m-%d %H:%M").to_string();
+    let conflicts = bundle.conflicts.len();
+    let import_path = bundle.path.clone();
+    let overwrite_path = bundle.path.clone();
+
+    rsx! {
+        div { class: "grid auto-rows-min gap-2 overflow-y-auto",
+            div { class: "grid grid-cols-[1fr_max-content] gap-2 items-center",
+                span { class: "text-sm", "Created {created} by {bundle.manifest.author}" }
+                ul { class: "menu menu-horizontal rounded-box gap-2 p-0",
+                    li {
+                        button {
+                            class: "btn btn-sm",
+                            onclick: move |_| router.do_send(SetupChange::ImportBundle(import_path.clone(), false)),
+                            if conflicts > 0 { "Import New Files" } else { "Import" }
+                        }
+                    }
+                    if conflicts > 0 {
+                        li {
+                            button {
+                                class: "btn btn-sm text-red",
+                                onclick: move |_| router.do_send(SetupChange::ImportBundle(overwrite_path.clone(), true)),
+                                "Import and Overwrite"
+                            }
+                        }
+                    }
+                }
+            }
+            { bundle.manifest.tracks.iter().map(|track| {
+                let label = format!("{} - {}", track.car_track.car, track.car_track.track);
+                rsx! {
+                    div { class: "grid gap-1 bg-surface0 rounded-md p-2",
+                        span { "{label}" }
+                        { track.files.iter().map(|name| {
+                            let conflict = bundle
+                                .conflicts
+                                .iter()
+                                .find(|c| c.car_track == track.car_track && c.name == *name)
+                                .cloned();
+                            rsx! { BundleFileRow { name: name.clone(), conflict } }
+                        })}
+                    }
+                }
+            })}
+        }
+    }
+}
+
+#[component]
+fn BundleFileRow(name: String, conflict: Option<BundleConflict>) -> Element {
+    let mut expanded = use_signal(|| false);
+
+    let Some(conflict) = conflict else {
+        return rsx! {
+            span { class: "text-sm text-green", "{name}" }
+        };
+    };
+
+    rsx! {
+        div { class: "grid gap-1",
+            div { class: "grid grid-cols-[1fr_max-content] gap-2 items-center",
+                span { class: "text-sm text-red", "{name} differs from the existing file" }
+                button {
+                    class: "btn btn-xs",
+                    disabled: conflict.changes.is_empty(),
+                    onclick: move |_| expanded.toggle(),
+                    "Compare"
+                }
+            }
+            if expanded() {
+                { conflict.changes.into_iter().map(|change| rsx! { FieldChangeRow { change } }) }
+            }
+        }
+    }
+}
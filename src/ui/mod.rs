@@ -1,4 +1,10 @@
-use components::{Base, Home, Settings, SettingsComponent, Setups};
+use components::{
+    batch_edit::BatchEditor,
+    history::{History, SessionView},
+    setup_editor::SetupEditor,
+    sharing::Sharing,
+    Base, Home, Settings, SettingsComponent, Setups,
+};
 use dioxus::{
     desktop::{tao::window::Icon, Config, LogicalSize, WindowBuilder},
     prelude::*,
@@ -8,7 +14,9 @@ use tracing::debug;
 use crate::{
     actors::{
         fuel_calculator::{FuelData, FuelMessage},
-        ui::{SessionInfo, UiState},
+        motec::MotecMessage,
+        setup_manager::SetupChange,
+        ui::{SessionHistory, SessionInfo, UiState},
         ClientManagement,
     },
     PROGRAM_NAME,
@@ -24,6 +32,16 @@ enum Route {
         Home {},
         #[route("/setups")]
         Setups {},
+        #[route("/setups/edit/:name")]
+        SetupEditor { name: String },
+        #[route("/sharing")]
+        Sharing {},
+        #[route("/batch")]
+        BatchEditor {},
+        #[route("/history")]
+        History {},
+        #[route("/history/:id")]
+        SessionView { id: String },
         #[route("/settings")]
         SettingsComponent {},
         // #[route("/debug")]
@@ -36,11 +54,7 @@ pub fn launch() -> () {
 
     #[cfg(windows)]
     {
-        let mut documents =
-            known_folders::get_known_folder_path(known_folders::KnownFolder::Documents).unwrap();
-        documents.push(PROGRAM_NAME);
-        documents.push("webview");
-        config = config.with_data_directory(documents)
+        config = config.with_data_directory(crate::data_folder().join("webview"))
     }
 
     let bin: &[u8] = include_bytes!("../../icons/icon.rgba");
@@ -86,6 +100,8 @@ fn App() -> Element {
         use_context_provider(|| SyncSignal::new_maybe_sync(crate::actors::ui::Setups::default()));
     let fuel_data: SyncSignal<FuelData> =
         use_context_provider(|| SyncSignal::new_maybe_sync(FuelData::default()));
+    let history: SyncSignal<SessionHistory> =
+        use_context_provider(|| SyncSignal::new_maybe_sync(SessionHistory::default()));
 
     // Initialize Main Arbiter & Background processes
     let arbiter = actix::Arbiter::new();
@@ -93,6 +109,8 @@ fn App() -> Element {
     let router = use_context_provider(|| router);
 
     router.do_send(FuelMessage::ReserveLaps(settings.read().reserve_laps));
+    router.do_send(SetupChange::TemperatureGrid(settings.read().temperature_grid()));
+    router.do_send(MotecMessage::Retention(settings.read().motec_retention()));
 
     // Initialize Main UI State and add client to backend
     let ui_state = UiState::initialize(
@@ -101,6 +119,7 @@ fn App() -> Element {
         laps.clone(),
         setups.clone(),
         fuel_data.clone(),
+        history.clone(),
     );
     router.do_send(ClientManagement::Add(ui_state.clone()));
     let _ = use_context_provider(|| ui_state);